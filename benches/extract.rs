@@ -0,0 +1,77 @@
+use std::fs;
+use std::path::Path;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use indexmap::IndexMap;
+use snippext::cmd::extract::extract;
+use snippext::constants::DEFAULT_TEMPLATE_IDENTIFIER;
+use snippext::settings::SnippextSettings;
+use snippext::types::SnippetSource;
+use tempfile::{tempdir, TempDir};
+
+const DIRECTORIES: usize = 20;
+const FILES_PER_DIRECTORY: usize = 100;
+const LINES_PER_FILE: usize = 200;
+
+/// Synthetic source tree where every tenth file contains a snippet
+fn source_tree() -> TempDir {
+    let dir = tempdir().unwrap();
+    for d in 0..DIRECTORIES {
+        let module_dir = dir.path().join(format!("module_{}", d));
+        fs::create_dir_all(&module_dir).unwrap();
+        for f in 0..FILES_PER_DIRECTORY {
+            let mut content = String::new();
+            let has_snippet = f % 10 == 0;
+            if has_snippet {
+                content.push_str(format!("// snippet::start snippet_{}_{}\n", d, f).as_str());
+            }
+
+            for l in 0..LINES_PER_FILE {
+                content.push_str(format!("    let value_{} = compute({});\n", l, l).as_str());
+            }
+
+            if has_snippet {
+                content.push_str("// snippet::end\n");
+            }
+
+            fs::write(module_dir.join(format!("file_{}.rs", f)), content).unwrap();
+        }
+    }
+
+    dir
+}
+
+fn settings(source_dir: &Path, output_dir: &Path, jobs: Option<usize>) -> SnippextSettings {
+    SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}"),
+        )]),
+        sources: vec![SnippetSource::new_local(vec![format!(
+            "{}/**/*.rs",
+            source_dir.to_string_lossy()
+        )])],
+        output_dir: Some(output_dir.to_string_lossy().to_string()),
+        enable_autodetect_language: false,
+        jobs,
+        ..Default::default()
+    }
+}
+
+fn bench_extract(c: &mut Criterion) {
+    let source_dir = source_tree();
+    let output_dir = tempdir().unwrap();
+
+    let mut group = c.benchmark_group("extract");
+    group.sample_size(10);
+    group.bench_function("synthetic_tree", |b| {
+        b.iter(|| extract(settings(source_dir.path(), output_dir.path(), None)).unwrap())
+    });
+    group.bench_function("synthetic_tree_single_job", |b| {
+        b.iter(|| extract(settings(source_dir.path(), output_dir.path(), Some(1))).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_extract);
+criterion_main!(benches);
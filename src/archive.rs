@@ -0,0 +1,84 @@
+use std::fs::File;
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use zip::ZipArchive;
+
+use crate::error::SnippextError;
+use crate::SnippextResult;
+
+/// Archive formats supported by archive snippet sources
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ArchiveFormat {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Determine archive format based on the file name, or URL path, of the archive
+    pub fn from_name(name: &str) -> Option<Self> {
+        // ignore any query string or fragment when archive is a URL
+        let name = name
+            .split(['?', '#'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// Unpack archive into `dir`. Entries that would be written outside of `dir` are skipped.
+pub(crate) fn unpack(archive: &Path, format: ArchiveFormat, dir: &Path) -> SnippextResult<()> {
+    let file = File::open(archive)?;
+    match format {
+        ArchiveFormat::Tar => tar::Archive::new(file).unpack(dir)?,
+        ArchiveFormat::TarGz => tar::Archive::new(GzDecoder::new(file)).unpack(dir)?,
+        ArchiveFormat::Zip => ZipArchive::new(file)
+            .and_then(|mut zip| zip.extract(dir))
+            .map_err(|e| {
+                SnippextError::GeneralError(format!(
+                    "Failed to unpack zip archive {}: {}",
+                    archive.to_string_lossy(),
+                    e
+                ))
+            })?,
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ArchiveFormat;
+
+    #[test]
+    fn archive_format_from_name() {
+        assert_eq!(
+            Some(ArchiveFormat::Zip),
+            ArchiveFormat::from_name("examples.zip")
+        );
+        assert_eq!(
+            Some(ArchiveFormat::TarGz),
+            ArchiveFormat::from_name("https://example.com/sdk-1.0.0.tar.gz?download=true")
+        );
+        assert_eq!(
+            Some(ArchiveFormat::TarGz),
+            ArchiveFormat::from_name("sdk-1.0.0.TGZ")
+        );
+        assert_eq!(
+            Some(ArchiveFormat::Tar),
+            ArchiveFormat::from_name("sdk.tar")
+        );
+        assert_eq!(None, ArchiveFormat::from_name("sdk.rs"));
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::constants::{SNIPPETS_INDEX_FILE, SNIPPETS_MARKDOWN_INDEX_FILE};
+use crate::types::Snippet;
+use crate::{files, SnippextResult};
+
+/// Bumped whenever the index format changes
+const CATALOG_VERSION: u32 = 1;
+
+/// Index of all extracted snippets written to the output directory so static site generators,
+/// e.g. Hugo or Docusaurus, can look up snippets and their generated files.
+#[derive(Debug, Serialize)]
+pub(crate) struct SnippetCatalog {
+    version: u32,
+    snippets: Vec<CatalogEntry>,
+}
+
+#[derive(Debug, Serialize)]
+struct CatalogEntry {
+    id: String,
+    path: String,
+    start_line: usize,
+    end_line: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lang: Option<String>,
+    attributes: BTreeMap<String, Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_link: Option<String>,
+    /// Generated files, relative to the output directory, by template identifier
+    files: IndexMap<String, String>,
+}
+
+impl SnippetCatalog {
+    pub fn new() -> Self {
+        Self {
+            version: CATALOG_VERSION,
+            snippets: Vec::new(),
+        }
+    }
+
+    /// Add a snippet along with the files, relative to the output directory, it was rendered to
+    pub fn add(&mut self, snippet: &Snippet, files: IndexMap<String, String>) {
+        self.snippets.push(CatalogEntry {
+            id: snippet.identifier.clone(),
+            path: snippet.path.to_string_lossy().to_string(),
+            start_line: snippet.start_line,
+            end_line: snippet.end_line,
+            lang: snippet
+                .attributes
+                .get("lang")
+                .and_then(Value::as_str)
+                .filter(|l| !l.is_empty())
+                .map(String::from),
+            attributes: snippet
+                .attributes
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            source_link: snippet.source_link.clone(),
+            files,
+        });
+    }
+
+    /// Write `snippets.json`, and `index.md` when `markdown` is set, to `output_dir`. Returns the
+    /// written file names along with whether their content changed.
+    pub fn save(
+        mut self,
+        output_dir: &Path,
+        markdown: bool,
+    ) -> SnippextResult<Vec<(&'static str, bool)>> {
+        self.snippets
+            .sort_by(|a, b| a.id.cmp(&b.id).then_with(|| a.path.cmp(&b.path)));
+
+        let mut written = Vec::new();
+        let content = serde_json::to_string_pretty(&self)?;
+        let changed =
+            files::write_if_changed(&output_dir.join(SNIPPETS_INDEX_FILE), content.as_bytes())?;
+        written.push((SNIPPETS_INDEX_FILE, changed));
+
+        if markdown {
+            let changed = files::write_if_changed(
+                &output_dir.join(SNIPPETS_MARKDOWN_INDEX_FILE),
+                self.to_markdown().as_bytes(),
+            )?;
+            written.push((SNIPPETS_MARKDOWN_INDEX_FILE, changed));
+        }
+
+        Ok(written)
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut markdown = String::from(
+            "# Snippets\n\n| Snippet | Source | Language | Files |\n| --- | --- | --- | --- |\n",
+        );
+        for entry in &self.snippets {
+            let location = format!("{}#L{}-L{}", entry.path, entry.start_line, entry.end_line);
+            let source = match &entry.source_link {
+                Some(link) => format!("[{}]({})", escape_cell(&location), link),
+                None => escape_cell(&location),
+            };
+            let files = entry
+                .files
+                .iter()
+                .map(|(template, file)| format!("[{}]({})", escape_cell(template), file))
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            let _ = writeln!(
+                markdown,
+                "| `{}` | {} | {} | {} |",
+                escape_cell(&entry.id),
+                source,
+                escape_cell(entry.lang.as_deref().unwrap_or_default()),
+                files
+            );
+        }
+
+        markdown
+    }
+}
+
+fn escape_cell(value: &str) -> String {
+    value.replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use indexmap::IndexMap;
+    use serde_json::Value;
+    use tempfile::tempdir;
+
+    use super::SnippetCatalog;
+    use crate::types::Snippet;
+
+    #[test]
+    fn should_write_sorted_index_and_markdown() {
+        let snippet = |id: &str| Snippet {
+            identifier: id.to_string(),
+            path: PathBuf::from("src/main.rs"),
+            text: String::new(),
+            attributes: HashMap::from([("lang".to_string(), Value::String("rust".to_string()))]),
+            start_line: 1,
+            end_line: 3,
+            source_link: None,
+            line_numbers: Vec::new(),
+        };
+
+        let mut catalog = SnippetCatalog::new();
+        for id in ["run", "main"] {
+            catalog.add(
+                &snippet(id),
+                IndexMap::from([(
+                    "default".to_string(),
+                    format!("src/main.rs/{}_default.md", id),
+                )]),
+            );
+        }
+
+        let dir = tempdir().unwrap();
+        let written = catalog.save(dir.path(), true).unwrap();
+        assert_eq!(vec![("snippets.json", true), ("index.md", true)], written);
+
+        let index: Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("snippets.json")).unwrap())
+                .unwrap();
+        assert_eq!("main", index["snippets"][0]["id"]);
+        assert_eq!("rust", index["snippets"][0]["lang"]);
+        assert_eq!(
+            "src/main.rs/run_default.md",
+            index["snippets"][1]["files"]["default"]
+        );
+
+        assert_eq!(
+            "# Snippets\n\n| Snippet | Source | Language | Files |\n| --- | --- | --- | --- |\n\
+             | `main` | src/main.rs#L1-L3 | rust | [default](src/main.rs/main_default.md) |\n\
+             | `run` | src/main.rs#L1-L3 | rust | [default](src/main.rs/run_default.md) |\n",
+            fs::read_to_string(dir.path().join("index.md")).unwrap()
+        );
+    }
+}
@@ -1,15 +1,20 @@
+use std::collections::HashMap;
 use std::fs;
-use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
 
 use clap::Parser;
 use config::{Config, Environment, File, FileFormat};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
 
+use crate::code_blocks::CodeBlocks;
 use crate::constants::{DEFAULT_SNIPPEXT_CONFIG, SNIPPEXT};
 use crate::error::SnippextError;
-use crate::types::SnippetCommentCache;
-use crate::{files, SnippextResult};
+use crate::files::{SnippextComment, TextFormat};
+use crate::types::{LineEnding, SnippetCommentCache};
+use crate::{code_blocks, files, manifest, SnippextResult};
 
 /// Clear snippets in target files
 #[derive(Clone, Debug, Parser)]
@@ -35,6 +40,11 @@ pub struct Args {
     /// Flag that will delete entire snippets, including the snippet comment, from target files
     #[arg(long)]
     pub delete: bool,
+
+    /// Flag that removes the files Snippext generated, as recorded by the output directory's
+    /// manifest, from the output directory
+    #[arg(long)]
+    pub output_dir: bool,
 }
 
 /// Settings that determine how snippets are cleared from target files
@@ -45,10 +55,28 @@ pub struct ClearSettings {
     /// Prefix that marks the end of a snippet.
     pub end: String,
     /// List of glob patters that contain the files to be spliced with the code snippets.
+    #[serde(default)]
     pub targets: Vec<String>,
     /// Determines whether the entire snippet should be removed. If false only the snippet content
     /// will be removed. If true the entire snippet will be removed.
+    #[serde(default)]
     pub delete: bool,
+    /// Directory in which generated snippet files are written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_dir: Option<String>,
+    /// Determines whether files generated in the output directory are removed. Only files recorded
+    /// in the output directory's manifest are removed.
+    #[serde(default)]
+    pub clear_output_dir: bool,
+    /// Line ending used when writing target files. Defaults to each target's existing line
+    /// ending.
+    #[serde(default)]
+    pub line_endings: Option<LineEnding>,
+    /// Determines whether snippet markers within fenced, delimited or literal code blocks of
+    /// Markdown, AsciiDoc and reStructuredText targets are cleared. Individual markers can opt
+    /// in with the `code_block` attribute.
+    #[serde(default)]
+    pub code_block_markers: bool,
 }
 
 /// Entry point for `clear` CLI command
@@ -74,6 +102,14 @@ fn build_clear_settings(opt: Args) -> SnippextResult<ClearSettings> {
         .set_override_option("end", opt.end)?
         .set_override_option("targets", opt.targets)?;
 
+    if opt.delete {
+        builder = builder.set_override("delete", true)?;
+    }
+
+    if opt.output_dir {
+        builder = builder.set_override("clear_output_dir", true)?;
+    }
+
     let settings: ClearSettings = builder.build()?.try_deserialize()?;
     Ok(settings)
 }
@@ -82,25 +118,45 @@ fn build_clear_settings(opt: Args) -> SnippextResult<ClearSettings> {
 pub fn clear(settings: ClearSettings) -> SnippextResult<()> {
     validate_clear_settings(&settings)?;
 
+    if settings.clear_output_dir {
+        if let Some(output_dir) = &settings.output_dir {
+            for path in manifest::clear_output_dir(Path::new(output_dir))? {
+                debug!("removed {}", path.to_string_lossy());
+            }
+        }
+    }
+
     let cache = SnippetCommentCache::new(settings.start, settings.end);
     for target in settings.targets {
         let extension = files::extension(target.as_str());
-        let snippet_comments = cache.get(extension);
+        let snippet_comments = cache.get(extension.clone());
 
-        let f = fs::File::open(&target)?;
-        let reader = BufReader::new(f);
+        let content = fs::read(&target)?;
+        let format = TextFormat::detect(&content).with_line_ending(settings.line_endings);
 
         let mut omit = false;
         let mut new_lines: Vec<String> = Vec::new();
-        for line in reader.lines() {
+        let mut code_blocks = CodeBlocks::new(&extension);
+        let markup = files::is_markup(&extension);
+        for line in TextFormat::strip_bom(&content).lines() {
             let l = line?;
-
-            if snippet_comments.is_line_start_snippet(l.as_str()).is_some() {
+            let (_, marker) = files::split_marker_prefix(&l, markup);
+            let in_code_block = !omit && code_blocks.contains(&l);
+
+            let start_comment = snippet_comments
+                .is_line_start_snippet(marker)
+                .filter(|comment| {
+                    !in_code_block
+                        || settings.code_block_markers
+                        || code_blocks::is_opted_in(marker_attributes(comment, marker).as_ref())
+                });
+
+            if start_comment.is_some() {
                 omit = true;
                 if !settings.delete {
                     new_lines.push(l.clone());
                 }
-            } else if snippet_comments.is_line_end_snippet(l.as_str()).is_some() {
+            } else if snippet_comments.is_line_end_snippet(marker).is_some() {
                 omit = false;
                 if !settings.delete {
                     new_lines.push(l.clone());
@@ -110,15 +166,18 @@ pub fn clear(settings: ClearSettings) -> SnippextResult<()> {
             }
         }
 
-        let new_content = new_lines
-            .into_iter()
-            .fold(String::new(), |content, s| content + s.as_str() + "\n");
-        fs::write(&target, new_content.as_bytes())?;
+        let new_content = format.join(&new_lines);
+        files::write_if_changed(Path::new(&target), new_content.as_bytes())?;
     }
 
     Ok(())
 }
 
+fn marker_attributes(comment: &SnippextComment, line: &str) -> Option<HashMap<String, Value>> {
+    let (_, attributes) = comment.captures(line)?;
+    serde_json::from_str(attributes?).ok()
+}
+
 fn validate_clear_settings(settings: &ClearSettings) -> SnippextResult<()> {
     let mut failures = vec![];
 
@@ -130,7 +189,11 @@ fn validate_clear_settings(settings: &ClearSettings) -> SnippextResult<()> {
         failures.push("end must not be empty".to_string())
     }
 
-    if settings.targets.is_empty() {
+    if settings.clear_output_dir {
+        if settings.output_dir.is_none() {
+            failures.push("output_dir is required to clear the output directory".to_string())
+        }
+    } else if settings.targets.is_empty() {
         failures.push("Must specify targets".to_string())
     }
 
@@ -155,7 +218,7 @@ mod tests {
     fn clear_target() {
         let mut target = NamedTempFile::new().unwrap();
         target
-            .write(
+            .write_all(
                 r#"# Some content
 # snippet::foo
 foo
@@ -172,6 +235,7 @@ More content
             end: "end::".to_string(),
             targets: vec![String::from(target.path().to_string_lossy())],
             delete: false,
+            ..Default::default()
         })
         .unwrap();
 
@@ -189,7 +253,7 @@ More content
     fn delete_target() {
         let mut target = NamedTempFile::new().unwrap();
         target
-            .write(
+            .write_all(
                 r#"# Some content
 # snippet::foo
 foo
@@ -206,6 +270,7 @@ More content
             end: "end::".to_string(),
             targets: vec![String::from(target.path().to_string_lossy())],
             delete: true,
+            ..Default::default()
         })
         .unwrap();
 
@@ -221,7 +286,7 @@ More content
     fn clear_target_starting_with_snippet() {
         let mut target = NamedTempFile::new().unwrap();
         target
-            .write(
+            .write_all(
                 r#"# snippet::foo
 # end::foo
 "#
@@ -234,6 +299,7 @@ More content
             end: "end::".to_string(),
             targets: vec![String::from(target.path().to_string_lossy())],
             delete: false,
+            ..Default::default()
         })
         .unwrap();
 
@@ -244,6 +310,135 @@ More content
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn clear_target_should_preserve_line_endings_and_bom() {
+        let mut target = NamedTempFile::new().unwrap();
+        target
+            .write_all(b"\xEF\xBB\xBF# snippet::foo\r\nfoo\r\n# end::foo\r\nMore content")
+            .unwrap();
+
+        super::clear(ClearSettings {
+            start: "snippet::".to_string(),
+            end: "end::".to_string(),
+            targets: vec![String::from(target.path().to_string_lossy())],
+            delete: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let actual = fs::read(target.path()).unwrap();
+        assert_eq!(
+            b"\xEF\xBB\xBF# snippet::foo\r\n# end::foo\r\nMore content".as_slice(),
+            actual
+        );
+    }
+
+    #[test]
+    fn clear_target_with_prefixed_markers() {
+        let mut target = NamedTempFile::new().unwrap();
+        target
+            .write_all(
+                r#"> # snippet::foo
+> foo
+> # end::foo
+"#
+                .as_bytes(),
+            )
+            .unwrap();
+
+        super::clear(ClearSettings {
+            start: "snippet::".to_string(),
+            end: "end::".to_string(),
+            targets: vec![String::from(target.path().to_string_lossy())],
+            delete: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let actual = fs::read_to_string(target.path()).unwrap();
+        let expected = r#"> # snippet::foo
+> # end::foo
+"#;
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn clear_target_should_ignore_markers_in_code_blocks() {
+        let mut target = tempfile::Builder::new().suffix(".md").tempfile().unwrap();
+        let content = r#"```md
+<!-- snippet::foo -->
+foo
+<!-- end::foo -->
+```
+"#;
+        target.write_all(content.as_bytes()).unwrap();
+
+        super::clear(ClearSettings {
+            start: "snippet::".to_string(),
+            end: "end::".to_string(),
+            targets: vec![String::from(target.path().to_string_lossy())],
+            delete: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(content, fs::read_to_string(target.path()).unwrap());
+    }
+
+    #[test]
+    fn clear_output_dir_should_only_remove_generated_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("main_default.md"), "").unwrap();
+        fs::write(dir.path().join("notes.md"), "").unwrap();
+        fs::write(
+            dir.path().join(".snippext-manifest.json"),
+            r#"{"version": 1, "files": ["main_default.md"]}"#,
+        )
+        .unwrap();
+
+        super::clear(ClearSettings {
+            start: "snippet::".to_string(),
+            end: "end::".to_string(),
+            output_dir: Some(dir.path().to_string_lossy().to_string()),
+            clear_output_dir: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert!(!dir.path().join("main_default.md").exists());
+        assert!(!dir.path().join(".snippext-manifest.json").exists());
+        assert!(dir.path().join("notes.md").exists());
+    }
+
+    #[test]
+    fn should_keep_configured_flags_when_cli_flags_are_not_set() {
+        let mut config = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        config
+            .write_all(
+                r#"start: "snippet::"
+end: "end::"
+targets: []
+delete: true
+clear_output_dir: true
+"#
+                .as_bytes(),
+            )
+            .unwrap();
+
+        let settings = super::build_clear_settings(super::Args {
+            config: Some(config.path().to_path_buf()),
+            start: None,
+            end: None,
+            targets: None,
+            delete: false,
+            output_dir: false,
+        })
+        .unwrap();
+
+        assert!(settings.delete);
+        assert!(settings.clear_output_dir);
+    }
+
     #[test]
     fn clear_target_should_require_non_empty_start_and_end() {
         let validation_result = super::clear(ClearSettings {
@@ -251,6 +446,7 @@ More content
             end: String::from(""),
             targets: vec!["".to_string()],
             delete: false,
+            ..Default::default()
         });
 
         let error = validation_result.err().unwrap();
@@ -259,7 +455,7 @@ More content
                 assert_eq!(2, failures.len());
                 assert_eq!(
                     String::from("start must not be empty"),
-                    failures.get(0).unwrap().to_string()
+                    failures.first().unwrap().to_string()
                 );
                 assert_eq!(
                     String::from("end must not be empty"),
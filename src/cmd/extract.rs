@@ -1,38 +1,47 @@
 use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::{Path, PathBuf};
+use std::io::{self, BufRead, Read};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
 use std::str::FromStr;
-use std::time::SystemTime;
 use std::{env, fs};
 
-use chrono::DateTime;
 use clap::ArgAction::SetTrue;
 use clap::Parser;
 use config::{Config, Environment, FileFormat};
-use filetime::{set_file_mtime, FileTime};
 use glob::{glob, Pattern};
-use regex::Regex;
-use reqwest::blocking::Client;
-use reqwest::header::{HeaderValue, EXPIRES, LAST_MODIFIED};
+use handlebars::Template;
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
 use serde_json::{json, Value};
-use tracing::warn;
+use tracing::{info, warn};
 use url::Url;
 use walkdir::WalkDir;
 
+use crate::archive::ArchiveFormat;
+use crate::catalog::SnippetCatalog;
+use crate::code_blocks::CodeBlocks;
 use crate::constants::{
     DEFAULT_GIT_BRANCH, DEFAULT_OUTPUT_FILE_EXTENSION, DEFAULT_SNIPPEXT_CONFIG,
-    DEFAULT_SOURCE_FILES, DEFAULT_TEMPLATE_IDENTIFIER, SNIPPEXT,
+    DEFAULT_SOURCE_FILES, DEFAULT_TEMPLATE_IDENTIFIER, SNIPPETS_INDEX_FILE,
+    SNIPPETS_MARKDOWN_INDEX_FILE, SNIPPEXT, SNIPPEXT_GROUP_ATTRIBUTE, STDIN_SOURCE,
 };
+use crate::encoding::{SkipReason, SkippedFiles};
 use crate::error::SnippextError;
-use crate::files::SnippextComment;
+use crate::files::{SnippextComment, TextFormat};
+use crate::groups::SnippetGroups;
+use crate::index::SnippextIndex;
+use crate::manifest::OutputManifest;
 use crate::sanitize::sanitize;
-use crate::templates::render_template;
+use crate::templates::{render_group_template, render_template, TemplateRegistry};
 use crate::types::{
     LinkFormat, MissingSnippet, MissingSnippetsBehavior, Snippet, SnippetCommentCache,
-    SnippetSource,
+    SnippetSource, TemplateEngine,
+};
+use crate::walk::SourceWalker;
+use crate::{
+    archive, code_blocks, encoding, files, git, http, manifest, templates, SnippextResult,
+    SnippextSettings,
 };
-use crate::{files, git, SnippextResult, SnippextSettings};
 
 /// Extracts snippets from source files and outputs and/or splices them into target files.
 #[derive(Clone, Debug, Parser)]
@@ -89,10 +98,15 @@ pub struct Args {
     pub targets: Vec<String>,
 
     /// List of glob patterns, separated by space, to look for snippets. Not applicable for
-    /// URL sources. Defaults to `**`.
+    /// URL sources. Use `-` to read snippets from stdin. Defaults to `**`.
     #[arg(short, long, value_delimiter = ' ')]
     pub sources: Vec<String>,
 
+    /// Language, name or file extension, of snippets read from stdin. Used to determine snippet
+    /// comments and the `lang` attribute
+    #[arg(long, value_name = "LANG")]
+    pub stdin_language: Option<String>,
+
     /// List of URLs, separated by space, to download and extract snippets from.
     /// URLs must return raw text in order for snippets to be successfully extracted.
     #[arg(long, value_delimiter = ' ')]
@@ -136,19 +150,51 @@ pub struct Args {
     /// is used to render snippets.
     #[arg(long, action = SetTrue)]
     pub selected_lines_include_ellipses: Option<bool>,
+
+    /// Number of threads used to extract snippets and process target files. Defaults to the
+    /// number of available CPUs.
+    #[arg(short, long, value_name = "N")]
+    pub jobs: Option<usize>,
+
+    /// Ignore the incremental index and extract from all sources and process all targets
+    #[arg(long)]
+    pub force: bool,
+
+    /// Flag that disables honoring `.gitignore`, `.ignore` and `.snippextignore` files when
+    /// walking sources
+    #[arg(long, action = SetTrue)]
+    pub no_ignore: Option<bool>,
+
+    /// Flag that removes files previously generated in the output directory that are no longer
+    /// produced, e.g. because a snippet was renamed or deleted
+    #[arg(long, action = SetTrue)]
+    pub prune: Option<bool>,
+}
+
+/// Outcome of an `extract` run
+#[derive(Clone, Debug, Default)]
+pub struct ExtractSummary {
+    /// Generated output files and targets whose content changed. Files with unchanged content
+    /// aren't written.
+    pub changed_files: Vec<PathBuf>,
+    /// Stale generated files removed from the output directory
+    pub removed_files: Vec<PathBuf>,
 }
 
 struct SnippetExtractionState {
     pub key: String,
     pub start_line: usize,
     pub lines: String,
+    /// Source line number of each appended line
+    pub line_numbers: Vec<usize>,
     pub attributes: HashMap<String, Value>,
     pub retain_nested_comments: bool,
 }
 
 impl SnippetExtractionState {
-    fn append_line(&mut self, line: &str) {
-        self.lines.push_str(line)
+    fn append_line(&mut self, line: &str, line_number: usize) {
+        self.lines.push_str(line);
+        self.line_numbers.push(line_number);
     }
 }
 
@@ -163,6 +209,7 @@ struct SourceFile {
 struct SourceLink {
     source_link: String,
     link_format: Option<LinkFormat>,
+    link_template: Option<String>,
 }
 
 impl SourceLink {
@@ -182,6 +229,7 @@ impl SourceLink {
         Self {
             source_link,
             link_format,
+            link_template: None,
         }
     }
 
@@ -214,6 +262,7 @@ impl SourceLink {
         Self {
             source_link,
             link_format,
+            link_template: None,
         }
     }
 
@@ -221,10 +270,40 @@ impl SourceLink {
         Self {
             source_link: url,
             link_format: None,
+            link_template: None,
+        }
+    }
+
+    /// Source link rendered from a handlebars template with `path`, `start_line` and `end_line`
+    /// available to it. `default_link` is used when no template is provided.
+    pub fn new_template(path: &str, link_template: Option<&String>, default_link: &str) -> Self {
+        match link_template {
+            Some(link_template) => Self {
+                source_link: path.to_string(),
+                link_format: None,
+                link_template: Some(link_template.to_string()),
+            },
+            None => Self::new_url(default_link.to_string()),
         }
     }
 
     pub fn append_lines(&self, start_line: usize, end_line: usize) -> String {
+        if let Some(link_template) = &self.link_template {
+            let data = HashMap::from([
+                ("path".to_string(), Value::String(self.source_link.clone())),
+                ("start_line".to_string(), Value::from(start_line)),
+                ("end_line".to_string(), Value::from(end_line)),
+            ]);
+
+            return match templates::render(link_template, &data) {
+                Ok(source_link) => source_link,
+                Err(e) => {
+                    warn!("failed to render source link template: {}", e);
+                    self.source_link.clone()
+                }
+            };
+        }
+
         if let Some(link_format) = self.link_format {
             match link_format {
                 LinkFormat::AzureRepos => {
@@ -249,56 +328,195 @@ impl SourceLink {
 
 /// Entry point for `extract` CLI command
 pub fn execute(extract_opt: Args) -> SnippextResult<()> {
+    let force = extract_opt.force;
     let settings = build_settings(extract_opt)?;
-    extract(settings)
+    if force {
+        if let Some(index_file) = settings.index_file.as_ref().map(Path::new) {
+            if index_file.exists() {
+                fs::remove_file(index_file)?;
+            }
+        }
+    }
+
+    let summary = extract(settings)?;
+    for path in &summary.changed_files {
+        info!("updated {}", path.to_string_lossy());
+    }
+
+    for path in &summary.removed_files {
+        info!("removed {}", path.to_string_lossy());
+    }
+
+    Ok(())
 }
 
-pub fn extract(snippext_settings: SnippextSettings) -> SnippextResult<()> {
+pub fn extract(snippext_settings: SnippextSettings) -> SnippextResult<ExtractSummary> {
     validate_snippext_settings(&snippext_settings)?;
 
-    let trim_chars: &[_] = &['.', '/'];
-    let extension = snippext_settings
-        .output_extension
-        .as_deref()
-        .unwrap_or(DEFAULT_OUTPUT_FILE_EXTENSION);
-
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(snippext_settings.jobs.unwrap_or_default())
+        .build()
+        .map_err(|e| SnippextError::GeneralError(format!("Failed to create thread pool: {}", e)))?;
+
+    let registry = TemplateRegistry::new(&snippext_settings)?;
+    let index = SnippextIndex::load(&snippext_settings);
+    let skipped = SkippedFiles::default();
+    let mut summary = ExtractSummary::default();
+    let mut generated_files = Vec::new();
+    let mut catalog = (snippext_settings.generate_snippets_index
+        && snippext_settings.output_dir.is_some())
+    .then(SnippetCatalog::new);
     let mut snippets = HashMap::new();
     let mut snippet_ids = HashSet::new();
+    let mut groups = SnippetGroups::new(snippext_settings.group_separator.clone());
     let cache = SnippetCommentCache::new(
         snippext_settings.start.clone(),
         snippext_settings.end.clone(),
     );
     for source in &snippext_settings.sources {
-        let extracted_snippets =
-            extract_snippets(source, &snippext_settings, &cache, &mut snippet_ids)?;
+        let source_settings = snippext_settings.with_source_overrides(source.overrides());
+        let source_cache =
+            SnippetCommentCache::new(source_settings.start.clone(), source_settings.end.clone());
+        let extracted_snippets = pool.install(|| {
+            extract_snippets(
+                source,
+                &source_settings,
+                &source_cache,
+                &index,
+                &skipped,
+                &mut snippet_ids,
+                &mut groups,
+            )
+        })?;
 
         if let Some(output_dir) = &snippext_settings.output_dir {
             let base_path = Path::new(output_dir.as_str());
-            for snippet in extracted_snippets.values() {
-                for identifier in snippext_settings.templates.keys() {
-                    let output_path = base_path
-                        .join(
-                            snippet
-                                .path
-                                .to_string_lossy()
-                                .trim_start_matches(trim_chars),
-                        )
-                        .join(format!("{}_{}", sanitize(&snippet.identifier), identifier))
-                        .with_extension(extension);
-
-                    fs::create_dir_all(output_path.parent().unwrap()).unwrap();
-                    let result =
-                        render_template(Some(identifier), snippet, &snippext_settings, None)?;
-                    fs::write(output_path, result).unwrap();
+            let rendered_snippets = pool.install(|| {
+                extracted_snippets
+                    .par_iter()
+                    .map(|snippet| {
+                        let mut output_files = Vec::new();
+                        for identifier in snippext_settings
+                            .templates
+                            .keys()
+                            .filter(|identifier| !templates::is_partial_only(identifier))
+                        {
+                            let output_path =
+                                output_path(base_path, snippet, identifier, &source_settings)?;
+
+                            let result = render_template(
+                                &registry,
+                                Some(identifier),
+                                snippet,
+                                &source_settings,
+                                None,
+                                None,
+                            )?;
+                            let changed = files::write_if_changed(&output_path, result.as_bytes())?;
+                            output_files.push((identifier, output_path, changed));
+                        }
+
+                        Ok((snippet, output_files))
+                    })
+                    .collect::<SnippextResult<Vec<_>>>()
+            })?;
+
+            let mut output_files = Vec::new();
+            for (snippet, snippet_files) in rendered_snippets {
+                if let Some(catalog) = catalog.as_mut() {
+                    catalog.add(
+                        snippet,
+                        snippet_files
+                            .iter()
+                            .map(|(identifier, path, _)| {
+                                (
+                                    identifier.to_string(),
+                                    relative_output_path(base_path, path),
+                                )
+                            })
+                            .collect(),
+                    );
+                }
+                output_files.extend(
+                    snippet_files
+                        .into_iter()
+                        .map(|(_, path, changed)| (path, changed)),
+                );
+            }
+
+            // sort for a stable summary
+            output_files.sort();
+            for (output_path, changed) in output_files {
+                if let Ok(relative_path) = output_path.strip_prefix(base_path) {
+                    generated_files.push(relative_path.to_string_lossy().to_string());
+                }
+
+                if changed {
+                    summary.changed_files.push(output_path);
+                }
+            }
+        }
+
+        // targets reference snippets by id so the last snippet extracted for an id is used
+        snippets.extend(
+            extracted_snippets
+                .into_iter()
+                .map(|snippet| (snippet.identifier.clone(), snippet)),
+        );
+    }
+
+    skipped.report();
+
+    if let Some(output_dir) = &snippext_settings.output_dir {
+        let output_dir = Path::new(output_dir.as_str());
+        if let Some(catalog) = catalog {
+            let mut catalog_files = vec![SNIPPETS_INDEX_FILE];
+            if snippext_settings.generate_markdown_index {
+                catalog_files.push(SNIPPETS_MARKDOWN_INDEX_FILE);
+            }
+
+            if let Some(file) = catalog_files
+                .into_iter()
+                .find(|file| generated_files.iter().any(|f| f == file))
+            {
+                return Err(SnippextError::GeneralError(format!(
+                    "snippet output file {} conflicts with the snippets index",
+                    output_dir.join(file).to_string_lossy()
+                )));
+            }
+
+            for (file, changed) in
+                catalog.save(output_dir, snippext_settings.generate_markdown_index)?
+            {
+                generated_files.push(file.to_string());
+                if changed {
+                    summary.changed_files.push(output_dir.join(file));
                 }
             }
         }
 
-        snippets.extend(extracted_snippets);
+        let previous_manifest = OutputManifest::load(output_dir);
+        let mut manifest = OutputManifest::new(generated_files);
+        if snippext_settings.prune_output_dir {
+            let stale_files = previous_manifest
+                .files()
+                .difference(manifest.files())
+                .collect::<Vec<&String>>();
+            summary.removed_files = manifest::remove_files(output_dir, stale_files)?;
+        } else {
+            // keep tracking previously generated files so they can be pruned or cleared later
+            manifest.extend(previous_manifest);
+        }
+
+        if output_dir.exists() {
+            manifest.save(output_dir)?;
+        }
     }
 
     if let Some(targets) = &snippext_settings.targets {
-        let mut missing_snippets = Vec::new();
+        // the same file can match multiple target globs but must only be processed once
+        let mut target_paths = Vec::new();
+        let mut seen_target_paths = HashSet::new();
         for target in targets {
             let globs = match glob(target.as_str()) {
                 Ok(paths) => paths,
@@ -312,12 +530,43 @@ pub fn extract(snippext_settings: SnippextSettings) -> SnippextResult<()> {
 
             for entry in globs {
                 let path = entry.unwrap();
-                let target_file_missing_snippets =
-                    process_target_file(path.as_path(), &snippets, &snippext_settings, &cache)?;
-                missing_snippets.extend(target_file_missing_snippets);
+                let canonical_path = fs::canonicalize(&path).unwrap_or(path.clone());
+                if seen_target_paths.insert(canonical_path) {
+                    target_paths.push(path);
+                }
             }
         }
 
+        let processed_targets = pool.install(|| {
+            target_paths
+                .par_iter()
+                .map(|path| {
+                    process_target_file(
+                        path.as_path(),
+                        &snippets,
+                        &snippext_settings,
+                        &registry,
+                        &groups,
+                        &cache,
+                        &index,
+                    )
+                })
+                .collect::<SnippextResult<Vec<ProcessedTarget>>>()
+        })?;
+
+        let mut missing_snippets = Vec::new();
+        for (path, processed_target) in target_paths.into_iter().zip(processed_targets) {
+            if processed_target.changed {
+                summary.changed_files.push(path);
+            }
+            missing_snippets.extend(processed_target.missing_snippets);
+            for warning in processed_target.warnings {
+                warn!("{}", warning);
+            }
+        }
+
+        index.save()?;
+
         match snippext_settings.missing_snippets_behavior {
             MissingSnippetsBehavior::Fail => {
                 return Err(SnippextError::MissingSnippetsError(missing_snippets));
@@ -334,9 +583,11 @@ pub fn extract(snippext_settings: SnippextSettings) -> SnippextResult<()> {
                 // do nothing
             }
         }
+    } else {
+        index.save()?;
     }
 
-    Ok(())
+    Ok(summary)
 }
 
 /// returns a list of validation failures
@@ -376,6 +627,8 @@ fn validate_snippext_settings(settings: &SnippextSettings) -> SnippextResult<()>
             }
         }
 
+        failures.extend(templates::template_errors(settings));
+
         if !has_default_template {
             failures.push(String::from("Must have one template named 'default'"));
         }
@@ -386,7 +639,37 @@ fn validate_snippext_settings(settings: &SnippextSettings) -> SnippextResult<()>
     } else {
         for (i, source) in settings.sources.iter().enumerate() {
             match source {
-                SnippetSource::Local { files } => {
+                SnippetSource::Archive {
+                    path_or_url, files, ..
+                } => {
+                    if path_or_url.is_empty() {
+                        failures.push(format!("sources[{}].path_or_url must not be empty", i));
+                    } else if ArchiveFormat::from_name(path_or_url).is_none() {
+                        failures.push(format!(
+                            "sources[{}].path_or_url must be a .zip, .tar, .tar.gz or .tgz archive",
+                            i
+                        ));
+                    }
+
+                    if files.is_empty() {
+                        failures.push(format!("sources[{}].files must not be empty", i));
+                    }
+
+                    for (j, pattern) in files.iter().enumerate() {
+                        if let Err(e) = Pattern::new(pattern) {
+                            failures.push(format!(
+                                "sources[{}].files[{}] is not a valid glob pattern. {}",
+                                i, j, e.msg
+                            ));
+                        }
+                    }
+                }
+                SnippetSource::Command { command, .. } => {
+                    if command.is_empty() {
+                        failures.push(format!("sources[{}].command must not be empty", i));
+                    }
+                }
+                SnippetSource::Local { files, .. } => {
                     if files.is_empty() {
                         failures.push(format!("sources[{}].files must not be empty", i));
                     }
@@ -406,6 +689,28 @@ fn validate_snippext_settings(settings: &SnippextSettings) -> SnippextResult<()>
                 }
                 _ => {}
             }
+
+            if let SnippetSource::Local { exclude, .. } | SnippetSource::Git { exclude, .. } =
+                source
+            {
+                for (j, pattern) in exclude.iter().enumerate() {
+                    if let Err(e) = Pattern::new(pattern) {
+                        failures.push(format!(
+                            "sources[{}].exclude[{}] is not a valid glob pattern. {}",
+                            i, j, e.msg
+                        ));
+                    }
+                }
+            }
+
+            let overrides = source.overrides();
+            if overrides.start.as_ref().is_some_and(|s| s.is_empty()) {
+                failures.push(format!("sources[{}].start must not be an empty string", i));
+            }
+
+            if overrides.end.as_ref().is_some_and(|s| s.is_empty()) {
+                failures.push(format!("sources[{}].end must not be an empty string", i));
+            }
         }
     }
 
@@ -421,6 +726,96 @@ fn validate_snippext_settings(settings: &SnippextSettings) -> SnippextResult<()>
         failures.push(String::from("output_extension must not be an empty string"));
     }
 
+    if let Some(output_path_template) = &settings.output_path_template {
+        if output_path_template.trim().is_empty() {
+            failures.push(String::from(
+                "output_path_template must not be an empty string",
+            ));
+        } else if let Err(e) = Template::compile(output_path_template) {
+            failures.push(format!(
+                "output_path_template is not a valid template. {}",
+                e
+            ));
+        }
+    }
+
+    if settings.generate_markdown_index && !settings.generate_snippets_index {
+        failures.push(String::from(
+            "generate_markdown_index requires generate_snippets_index",
+        ));
+    }
+
+    for template in settings.template_engines.keys() {
+        if !settings.templates.contains_key(template) {
+            failures.push(format!(
+                "template_engines[{}] does not match a template",
+                template
+            ));
+        }
+    }
+
+    for (target, template) in &settings.target_templates {
+        if target.trim_start_matches('.').is_empty() {
+            failures.push(String::from(
+                "target_templates keys must not be empty strings",
+            ));
+        } else if let Err(e) = Pattern::new(target.trim_start_matches("./")) {
+            failures.push(format!(
+                "target_templates[{}] is not a valid glob pattern. {}",
+                target, e.msg
+            ));
+        }
+
+        if let Some(error) = templates::unknown_template(template, settings) {
+            failures.push(format!("target_templates[{}] {}", target, error));
+        }
+    }
+
+    if settings.tab_width == 0 {
+        failures.push(String::from("tab_width must be greater than 0"));
+    }
+
+    if settings
+        .group_separator
+        .as_ref()
+        .is_some_and(|s| s.is_empty())
+    {
+        failures.push(String::from("group_separator must not be an empty string"));
+    }
+
+    if let Some(error) = settings
+        .group_template
+        .as_deref()
+        .and_then(|template| templates::unknown_template(template, settings))
+    {
+        failures.push(format!("group_template {}", error));
+    }
+
+    for (template, extension) in &settings.output_extensions {
+        if !settings.templates.contains_key(template) {
+            failures.push(format!(
+                "output_extensions[{}] does not match a template",
+                template
+            ));
+        }
+
+        if extension.is_empty() {
+            failures.push(format!(
+                "output_extensions[{}] must not be an empty string",
+                template
+            ));
+        }
+    }
+
+    if let Some(fallback_encoding) = &settings.fallback_encoding {
+        if encoding::encoding_for_label(fallback_encoding).is_none() {
+            failures.push(format!(
+                "fallback_encoding `{}` is not a supported encoding",
+                fallback_encoding
+            ));
+        }
+    }
+
     if failures.is_empty() {
         Ok(())
     } else {
@@ -432,45 +827,204 @@ fn extract_snippets(
     source: &SnippetSource,
     settings: &SnippextSettings,
     cache: &SnippetCommentCache,
-    snippet_ids: &mut HashSet<String>,
-) -> SnippextResult<HashMap<String, Snippet>> {
-    let mut snippets = HashMap::new();
+    index: &SnippextIndex,
+    skipped: &SkippedFiles,
+    snippet_ids: &mut HashSet<(String, String)>,
+    groups: &mut SnippetGroups,
+) -> SnippextResult<Vec<Snippet>> {
+    let mut snippets = Vec::new();
     match source {
-        SnippetSource::Local { files } => {
+        SnippetSource::Archive {
+            path_or_url,
+            files,
+            strip_prefix,
+            source_link_template,
+            ..
+        } => {
+            let format = ArchiveFormat::from_name(path_or_url).ok_or(
+                SnippextError::GeneralError(format!("Unsupported archive {}", path_or_url)),
+            )?;
+
+            let archive_path = if path_or_url.starts_with("http") {
+                get_source_file_from_url(path_or_url)?.full_path
+            } else {
+                PathBuf::from(path_or_url)
+            };
+
+            // unpacked archive contents are removed once the TempDir is dropped
+            let unpack_dir = tempfile::Builder::new()
+                .prefix("archive")
+                .tempdir_in(get_download_directory()?)?;
+            archive::unpack(&archive_path, format, unpack_dir.path())?;
+
+            let patterns = files
+                .iter()
+                .map(|f| {
+                    Pattern::new(f).map_err(|err| {
+                        SnippextError::GlobPatternError(format!(
+                            "Glob pattern error for `{}`. {}",
+                            f, err.msg
+                        ))
+                    })
+                })
+                .collect::<SnippextResult<Vec<Pattern>>>()?;
+
+            let strip_prefix = strip_prefix
+                .as_deref()
+                .map(|p| p.trim_matches('/'))
+                .filter(|p| !p.is_empty());
+
+            let mut source_files = Vec::new();
+            for entry in WalkDir::new(unpack_dir.path())
+                .sort_by_file_name()
+                .into_iter()
+                .filter_map(Result::ok)
+                .filter(|e| e.file_type().is_file())
+            {
+                let entry_path = entry.path().strip_prefix(unpack_dir.path())?;
+                // prefix is removed by whole path components so `sdk-1.0.0` doesn't match
+                // `sdk-1.0.0-extra/`. Entries outside of the prefix are skipped
+                let relative_path = match strip_prefix {
+                    Some(prefix) => match entry_path.strip_prefix(prefix) {
+                        Ok(p) => p,
+                        Err(_) => continue,
+                    },
+                    None => entry_path,
+                };
+                let relative_path_str = relative_path.to_string_lossy().replace('\\', "/");
+                let relative_path_str = relative_path_str.as_str();
+
+                if patterns.iter().any(|p| p.matches(relative_path_str)) {
+                    source_files.push(SourceFile {
+                        full_path: entry.path().to_path_buf(),
+                        relative_path: PathBuf::from(relative_path_str),
+                        source_link: SourceLink::new_template(
+                            relative_path_str,
+                            source_link_template.as_ref(),
+                            path_or_url,
+                        ),
+                    });
+                }
+            }
+
+            // entries are unpacked into a new directory on every run so they never match the index
+            let extracted_snippets = extract_snippets_from_files(
+                source_files,
+                settings,
+                cache,
+                &SnippextIndex::disabled(),
+                skipped,
+            )?;
+            insert_snippets(&mut snippets, extracted_snippets, snippet_ids, groups);
+        }
+        SnippetSource::Command {
+            command,
+            args,
+            cwd,
+            language,
+            ..
+        } => {
+            let mut process = Command::new(command);
+            process.args(args);
+            if let Some(cwd) = cwd {
+                process.current_dir(cwd);
+            }
+
+            let output = process.output()?;
+            if !output.status.success() {
+                return Err(SnippextError::GeneralError(format!(
+                    "Command `{}` failed with {}: {}",
+                    command,
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+
+            let source_file = SourceFile {
+                full_path: PathBuf::from(command),
+                relative_path: PathBuf::from(command),
+                source_link: SourceLink::new_local(command, None, None),
+            };
+
+            let extracted_snippets = extract_snippets_from_content(
+                &output.stdout,
+                source_file,
+                language.as_deref(),
+                settings,
+                cache,
+                skipped,
+            )?;
+
+            insert_snippets(&mut snippets, vec![extracted_snippets], snippet_ids, groups);
+        }
+        SnippetSource::Local {
+            files,
+            exclude,
+            stdin_language,
+            ..
+        } => {
+            let walker = SourceWalker::new(
+                settings.respect_ignore_files,
+                exclude,
+                &excluded_dirs(settings),
+            )?;
+            let mut source_snippets = Vec::new();
             for file in files {
-                let paths = glob(file.as_str()).map_err(|e| {
-                    SnippextError::GlobPatternError(format!(
-                        "Glob pattern error for `{}`. {}",
-                        file, e.msg
-                    ))
-                })?;
-
-                for entry in paths {
-                    let path = entry.unwrap();
-                    if !path.is_dir() {
-                        let source_file = SourceFile {
-                            full_path: path.clone(),
-                            relative_path: path.clone(),
-                            source_link: SourceLink::new_local(
-                                path.to_string_lossy().as_ref(),
-                                settings.source_link_prefix.as_deref(),
-                                settings.link_format,
-                            ),
-                        };
-
-                        let extracted_snippets =
-                            extract_snippets_from_file(source_file, settings, cache, snippet_ids)?;
-
-                        snippets.extend(extracted_snippets);
-                    }
+                if file == STDIN_SOURCE {
+                    let source_file = SourceFile {
+                        full_path: PathBuf::from("stdin"),
+                        relative_path: PathBuf::from("stdin"),
+                        source_link: SourceLink::new_local("stdin", None, None),
+                    };
+
+                    let mut content = Vec::new();
+                    io::stdin().lock().read_to_end(&mut content)?;
+
+                    let extracted_snippets = extract_snippets_from_content(
+                        &content,
+                        source_file,
+                        stdin_language.as_deref(),
+                        settings,
+                        cache,
+                        skipped,
+                    )?;
+
+                    source_snippets.push(extracted_snippets);
+                    continue;
                 }
+
+                let source_files = walker
+                    .glob(file.as_str())?
+                    .into_iter()
+                    .map(|path| SourceFile {
+                        full_path: path.clone(),
+                        relative_path: path.clone(),
+                        source_link: SourceLink::new_local(
+                            path.to_string_lossy().as_ref(),
+                            settings.source_link_prefix.as_deref(),
+                            settings.link_format,
+                        ),
+                    })
+                    .collect();
+
+                source_snippets.extend(extract_snippets_from_files(
+                    source_files,
+                    settings,
+                    cache,
+                    index,
+                    skipped,
+                )?);
             }
+
+            insert_snippets(&mut snippets, source_snippets, snippet_ids, groups);
         }
         SnippetSource::Git {
             repository,
             branch,
             cone_patterns,
             files,
+            exclude,
+            ..
         } => {
             let repository_url =
                 Url::from_str(repository).expect("Git repository must be a valid URL");
@@ -479,7 +1033,7 @@ fn extract_snippets(
                     .file_stem()
                     .ok_or(SnippextError::GeneralError(format!(
                         "Could not get repository name from {}",
-                        &repository
+                        repository
                     )))?;
             let download_dir = get_download_directory()?.join(repo_name);
 
@@ -496,7 +1050,7 @@ fn extract_snippets(
                 &download_dir,
             )?;
 
-            let dir_length = download_dir.to_string_lossy().len();
+            let walker = SourceWalker::new(settings.respect_ignore_files, exclude, &[])?;
             let patterns = files
                 .iter()
                 .map(|f| Pattern::new(f))
@@ -514,17 +1068,13 @@ fn extract_snippets(
                 LinkFormat::from_domain(domain)
             });
 
-            for entry in WalkDir::new(&download_dir)
-                .into_iter()
-                .filter_map(Result::ok)
-                .filter(|e| e.file_type().is_file())
-            {
-                let path = &entry.path().to_string_lossy().to_string();
-                let relative_path_str = &path[dir_length..];
+            let mut source_files = Vec::new();
+            for relative_path in walker.walk(&download_dir) {
+                let relative_path_str = &format!("/{}", relative_path.to_string_lossy());
 
                 if patterns.iter().any(|p| p.matches(relative_path_str)) {
-                    let source_file = SourceFile {
-                        full_path: entry.path().to_path_buf(),
+                    source_files.push(SourceFile {
+                        full_path: download_dir.join(&relative_path),
                         relative_path: PathBuf::from(relative_path_str),
                         source_link: SourceLink::new_git(
                             repository,
@@ -532,27 +1082,130 @@ fn extract_snippets(
                             relative_path_str,
                             link_format,
                         ),
-                    };
-
-                    let extracted_snippets =
-                        extract_snippets_from_file(source_file, settings, cache, snippet_ids)?;
-
-                    snippets.extend(extracted_snippets);
+                    });
                 }
             }
+
+            let extracted_snippets =
+                extract_snippets_from_files(source_files, settings, cache, index, skipped)?;
+            insert_snippets(&mut snippets, extracted_snippets, snippet_ids, groups);
         }
-        SnippetSource::Url { url } => {
+        SnippetSource::Url { url, .. } => {
             let source_file = get_source_file_from_url(url)?;
             let extracted_snippets =
-                extract_snippets_from_file(source_file, settings, cache, snippet_ids)?;
-
-            snippets.extend(extracted_snippets);
+                extract_snippets_from_file(source_file, settings, cache, index, skipped)?;
+            insert_snippets(&mut snippets, vec![extracted_snippets], snippet_ids, groups);
         }
     }
 
     Ok(snippets)
 }
 
+/// Generated file path relative to the output directory using `/` separators
+fn relative_output_path(output_dir: &Path, path: &Path) -> String {
+    path.strip_prefix(output_dir)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Path of the file a snippet is rendered to with the given template. Defaults to
+/// `<source path>/<id>_<template>.<extension>` within the output directory unless
+/// `output_path_template` is set.
+fn output_path(
+    output_dir: &Path,
+    snippet: &Snippet,
+    template: &str,
+    settings: &SnippextSettings,
+) -> SnippextResult<PathBuf> {
+    let extension = settings
+        .output_extensions
+        .get(template)
+        .or(settings.output_extension.as_ref())
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_OUTPUT_FILE_EXTENSION);
+    let source_path = snippet.path.to_string_lossy();
+    let source_path = Path::new(source_path.trim_start_matches(['.', '/']));
+    let id = sanitize(&snippet.identifier);
+
+    let Some(path_template) = &settings.output_path_template else {
+        return Ok(output_dir
+            .join(source_path)
+            .join(format!("{}_{}", id, template))
+            .with_extension(extension));
+    };
+
+    let data = HashMap::from([
+        ("id".to_string(), Value::String(id)),
+        ("template".to_string(), Value::String(template.to_string())),
+        (
+            "path".to_string(),
+            Value::String(source_path.to_string_lossy().to_string()),
+        ),
+        (
+            "dir".to_string(),
+            Value::String(
+                source_path
+                    .parent()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            ),
+        ),
+        (
+            "filename".to_string(),
+            Value::String(
+                source_path
+                    .file_name()
+                    .map(|f| f.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            ),
+        ),
+        (
+            "lang".to_string(),
+            snippet
+                .attributes
+                .get("lang")
+                .cloned()
+                .unwrap_or(Value::String(String::new())),
+        ),
+        ("ext".to_string(), Value::String(extension.to_string())),
+    ]);
+
+    let rendered = templates::render(path_template, &data)?;
+    let relative_path = PathBuf::from(rendered.trim());
+    let within_output_dir = relative_path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if relative_path.file_name().is_none() || !within_output_dir {
+        return Err(SnippextError::GeneralError(format!(
+            "output path `{}` for snippet {} must be a file path relative to output_dir",
+            rendered, snippet.identifier
+        )));
+    }
+
+    Ok(output_dir.join(relative_path))
+}
+
+/// Directories that are never walked for source files given they contain Snippext output, i.e.
+/// the output directory and the directory of the index file
+fn excluded_dirs(settings: &SnippextSettings) -> Vec<&str> {
+    let index_dir = settings
+        .index_file
+        .as_deref()
+        .and_then(|f| Path::new(f).parent())
+        .and_then(Path::to_str)
+        .filter(|d| !d.is_empty());
+
+    settings
+        .output_dir
+        .as_deref()
+        .into_iter()
+        .chain(index_dir)
+        .collect()
+}
+
 fn get_download_directory() -> SnippextResult<PathBuf> {
     let snippext_dir = env::temp_dir().join("snippext");
     if !snippext_dir.exists() {
@@ -591,53 +1244,7 @@ fn get_source_file_from_url(url: &String) -> SnippextResult<SourceFile> {
     ))?;
     fs::create_dir_all(parent_dirs)?;
 
-    if let Ok(file_metadata) = download_path.metadata() {
-        let file_modified = file_metadata.modified().ok();
-        if file_modified.is_some_and(|t| t > SystemTime::now()) {
-            return Ok(SourceFile {
-                full_path: download_path,
-                relative_path: url_file_path,
-                source_link: SourceLink::new_url(url.to_string()),
-            });
-        }
-    }
-
-    let client = Client::new();
-    let head = client.head(url).send()?;
-    if !head.status().is_success() {
-        // TODO: log
-        return Err(SnippextError::GeneralError(format!(
-            "Failed to download details from {}",
-            url
-        )));
-    }
-
-    if let Ok(file_metadata) = download_path.metadata() {
-        if let Ok(file_created) = file_metadata.created() {
-            let web_modified = header_to_systemtime(head.headers().get(LAST_MODIFIED));
-            if web_modified.is_some_and(|t| t < file_created) {
-                return Ok(SourceFile {
-                    full_path: download_path,
-                    relative_path: url_file_path,
-                    source_link: SourceLink::new_url(url.to_string()),
-                });
-            }
-        }
-
-        fs::remove_file(&download_path)?;
-    }
-
-    let mut response = client.get(url).send()?;
-    if response.status().is_success() {
-        let mut file = File::create(&download_path)?;
-
-        response.copy_to(&mut file)?;
-
-        let web_expiration = header_to_systemtime(response.headers().get(EXPIRES));
-        if let Some(expires) = web_expiration {
-            set_file_mtime(&download_path, FileTime::from(expires))?;
-        }
-    }
+    http::download(url, &download_path)?;
 
     Ok(SourceFile {
         full_path: download_path,
@@ -646,27 +1253,107 @@ fn get_source_file_from_url(url: &String) -> SnippextResult<SourceFile> {
     })
 }
 
-// Wed, 16 Aug 2023 22:40:19 GMT
-fn header_to_systemtime(header_value: Option<&HeaderValue>) -> Option<SystemTime> {
-    let header_value_str = header_value?.to_str().ok()?;
-    let date_time = DateTime::parse_from_rfc2822(header_value_str).ok()?;
-    Some(date_time.into())
+/// Extract snippets from source files in parallel. Snippets are returned per source file in the
+/// same order as `source_files` so results are deterministic regardless of scheduling.
+fn extract_snippets_from_files(
+    source_files: Vec<SourceFile>,
+    settings: &SnippextSettings,
+    cache: &SnippetCommentCache,
+    index: &SnippextIndex,
+    skipped: &SkippedFiles,
+) -> SnippextResult<Vec<Vec<Snippet>>> {
+    source_files
+        .into_par_iter()
+        .map(|source_file| extract_snippets_from_file(source_file, settings, cache, index, skipped))
+        .collect()
+}
+
+/// Add extracted snippets, in order, to their groups warning about duplicate snippet identifiers.
+/// Snippets sharing an identifier in different languages are expected for snippet groups and only
+/// warned about when a marker that isn't a group marker renders them.
+/// Add extracted snippets to a source's snippets. Snippets sharing an id but written in different
+/// languages are all kept while a later snippet with the same id and language replaces the earlier
+/// one.
+fn insert_snippets(
+    snippets: &mut Vec<Snippet>,
+    extracted_snippets: Vec<Vec<Snippet>>,
+    snippet_ids: &mut HashSet<(String, String)>,
+    groups: &mut SnippetGroups,
+) {
+    for snippet in extracted_snippets.into_iter().flatten() {
+        let new_id = snippet_ids.insert((snippet.identifier.clone(), snippet_lang(&snippet)));
+        if !new_id {
+            warn!("multiple snippets with id {} found", &snippet.identifier);
+        }
+
+        groups.add(&snippet);
+        match snippets.iter_mut().find(|s| {
+            !new_id
+                && s.identifier == snippet.identifier
+                && snippet_lang(s) == snippet_lang(&snippet)
+        }) {
+            Some(existing) => *existing = snippet,
+            None => snippets.push(snippet),
+        }
+    }
+}
+
+fn snippet_lang(snippet: &Snippet) -> String {
+    snippet
+        .attributes
+        .get("lang")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
 }
 
 fn extract_snippets_from_file(
     source_file: SourceFile,
     settings: &SnippextSettings,
     cache: &SnippetCommentCache,
-    snippet_ids: &mut HashSet<String>,
-) -> SnippextResult<HashMap<String, Snippet>> {
-    let f = File::open(&source_file.full_path)?;
-    let reader = BufReader::new(f);
+    index: &SnippextIndex,
+    skipped: &SkippedFiles,
+) -> SnippextResult<Vec<Snippet>> {
+    let metadata = fs::metadata(&source_file.full_path)?;
+    if let Some(max_file_size) = settings.max_file_size {
+        if metadata.len() > max_file_size {
+            skipped.skip(
+                &source_file.full_path,
+                SkipReason::TooLarge {
+                    size: metadata.len(),
+                    max_file_size,
+                },
+            );
+            return Ok(Vec::new());
+        }
+    }
+
+    if let Some(snippets) = index.unchanged_source(&source_file.full_path, &metadata) {
+        return Ok(snippets);
+    }
+
+    let content = fs::read(&source_file.full_path)?;
+    if let Some(snippets) = index.unchanged_content(&source_file.full_path, &metadata, &content) {
+        return Ok(snippets);
+    }
+
+    let text = match encoding::decode(&content, fallback_encoding(settings)) {
+        Ok(text) => text,
+        Err(reason) => {
+            skipped.skip(&source_file.full_path, reason);
+            index.record_source(&source_file.full_path, &metadata, &content, &[]);
+            return Ok(Vec::new());
+        }
+    };
 
-    let mut current_line_number = 0;
-    let mut state: Vec<SnippetExtractionState> = Vec::new();
-    let mut snippets = HashMap::new();
     let extension = files::extension_from_path(&source_file.full_path);
-    let snippet_comments = cache.get(extension);
+    if !cache
+        .get(extension.clone())
+        .may_contain_snippets(text.as_bytes())
+    {
+        index.record_source(&source_file.full_path, &metadata, &content, &[]);
+        return Ok(Vec::new());
+    }
 
     let language = if settings.enable_autodetect_language {
         match hyperpolyglot::detect(&source_file.full_path) {
@@ -683,6 +1370,85 @@ fn extract_snippets_from_file(
         None
     };
 
+    let full_path = source_file.full_path.clone();
+    let snippets = extract_snippets_from_reader(
+        text.as_bytes(),
+        source_file,
+        extension,
+        language,
+        settings,
+        cache,
+    )?;
+
+    index.record_source(&full_path, &metadata, &content, &snippets);
+    Ok(snippets)
+}
+
+/// Extract snippets from content that isn't read from a source file, e.g. command output or stdin,
+/// which is size limited and decoded the same as source files. `language` determines snippet
+/// comments and the `lang` attribute given there is no file extension.
+fn extract_snippets_from_content(
+    content: &[u8],
+    source_file: SourceFile,
+    language: Option<&str>,
+    settings: &SnippextSettings,
+    cache: &SnippetCommentCache,
+    skipped: &SkippedFiles,
+) -> SnippextResult<Vec<Snippet>> {
+    if let Some(max_file_size) = settings.max_file_size {
+        let size = content.len() as u64;
+        if size > max_file_size {
+            skipped.skip(
+                &source_file.full_path,
+                SkipReason::TooLarge {
+                    size,
+                    max_file_size,
+                },
+            );
+            return Ok(Vec::new());
+        }
+    }
+
+    let text = match encoding::decode(content, fallback_encoding(settings)) {
+        Ok(text) => text,
+        Err(reason) => {
+            skipped.skip(&source_file.full_path, reason);
+            return Ok(Vec::new());
+        }
+    };
+
+    extract_snippets_from_reader(
+        text.as_bytes(),
+        source_file,
+        language
+            .map(files::extension_from_language)
+            .unwrap_or_default(),
+        language.map(str::to_ascii_lowercase),
+        settings,
+        cache,
+    )
+}
+
+fn fallback_encoding(settings: &SnippextSettings) -> Option<&'static encoding_rs::Encoding> {
+    settings
+        .fallback_encoding
+        .as_deref()
+        .and_then(encoding::encoding_for_label)
+}
+
+fn extract_snippets_from_reader<R: BufRead>(
+    reader: R,
+    source_file: SourceFile,
+    extension: String,
+    language: Option<String>,
+    settings: &SnippextSettings,
+    cache: &SnippetCommentCache,
+) -> SnippextResult<Vec<Snippet>> {
+    let mut current_line_number = 0;
+    let mut state: Vec<SnippetExtractionState> = Vec::new();
+    let mut snippets = Vec::new();
+    let snippet_comments = cache.get(extension);
+
     for line in reader.lines() {
         current_line_number += 1;
         let l = line?;
@@ -729,7 +1495,7 @@ fn extract_snippets_from_file(
             if !state.is_empty() {
                 for app_state in state.iter_mut() {
                     if app_state.retain_nested_comments {
-                        app_state.append_line((l.clone() + "\n").as_str());
+                        app_state.append_line((l.clone() + "\n").as_str(), current_line_number);
                     }
                 }
             }
@@ -738,6 +1504,7 @@ fn extract_snippets_from_file(
                 key,
                 start_line: current_line_number,
                 lines: String::new(),
+                line_numbers: Vec::new(),
                 attributes,
                 retain_nested_comments,
             });
@@ -754,36 +1521,30 @@ fn extract_snippets_from_file(
             if let Some(snippet_extraction_state) = state.pop() {
                 let id = snippet_extraction_state.key;
 
-                snippets.insert(
-                    id.clone(),
-                    Snippet {
-                        identifier: id.clone(),
-                        path: source_file.relative_path.to_owned(),
-                        text: snippet_extraction_state.lines,
-                        attributes: snippet_extraction_state.attributes,
-                        start_line: snippet_extraction_state.start_line,
-                        end_line: current_line_number,
-                        source_link: Some(source_file.source_link.append_lines(
-                            snippet_extraction_state.start_line,
-                            current_line_number,
-                        )),
-                    },
-                );
-
-                let new_id = snippet_ids.insert(id.clone());
-                if !new_id {
-                    warn!("multiple snippets with id {} found", id.clone());
-                }
+                snippets.push(Snippet {
+                    identifier: id,
+                    path: source_file.relative_path.to_owned(),
+                    text: snippet_extraction_state.lines,
+                    attributes: snippet_extraction_state.attributes,
+                    start_line: snippet_extraction_state.start_line,
+                    end_line: current_line_number,
+                    source_link: Some(
+                        source_file
+                            .source_link
+                            .append_lines(snippet_extraction_state.start_line, current_line_number),
+                    ),
+                    line_numbers: snippet_extraction_state.line_numbers,
+                });
 
                 for app_state in state.iter_mut() {
                     if app_state.retain_nested_comments {
-                        app_state.append_line((l.clone() + "\n").as_str());
+                        app_state.append_line((l.clone() + "\n").as_str(), current_line_number);
                     }
                 }
             }
         } else {
             for e in state.iter_mut() {
-                e.append_line((l.clone() + "\n").as_str())
+                e.append_line((l.clone() + "\n").as_str(), current_line_number)
             }
         }
     }
@@ -792,9 +1553,9 @@ fn extract_snippets_from_file(
         let snippet = state.pop().unwrap();
         return Err(SnippextError::GeneralError(format!(
             "Snippet '{}' was not closed in file {} starting at line {}",
-            &snippet.key,
-            &source_file.relative_path.to_string_lossy(),
-            &snippet.start_line
+            snippet.key,
+            source_file.relative_path.to_string_lossy(),
+            snippet.start_line
         )));
     }
 
@@ -805,67 +1566,117 @@ fn extract_id_and_attributes(
     line: &str,
     comment: &SnippextComment,
 ) -> SnippextResult<(String, Option<HashMap<String, Value>>)> {
-    let regex_close = comment
-        .start_close
-        .as_ref()
-        .map(|s| format!("({}|$)", s))
-        .unwrap_or("$".to_string());
+    let Some((key, attributes)) = comment.captures(line) else {
+        return Err(SnippextError::GeneralError(format!(
+            "could not extract snippet details from {}",
+            line
+        )));
+    };
 
-    let format = format!(
-        "{}[ ]*(?P<key>[\\S]*)(?P<attributes>.*?){}",
-        comment.start, regex_close
-    );
+    // markers without a key, e.g. `snippet::start {"group": "connect"}`, only have attributes
+    if key.starts_with('{') {
+        let attributes = format!("{} {}", key, attributes.unwrap_or_default());
+        return Ok((String::new(), Some(serde_json::from_str(&attributes)?)));
+    }
 
-    let re = Regex::new(&format).unwrap();
-    let captures = re.captures(line);
-    if let Some(capture_groups) = captures {
-        let Some(key) = capture_groups.name("key") else {
-            return Err(SnippextError::GeneralError(format!(
-                "could not extract key from {}",
-                line
-            )));
-        };
+    let attributes = match attributes {
+        Some(attributes) => Some(serde_json::from_str(attributes)?),
+        None => None,
+    };
 
-        let attributes = if let Some(match_attributes) = capture_groups.name("attributes") {
-            let attributes_str = match_attributes.as_str().trim();
-            if attributes_str.is_empty() {
-                None
-            } else {
-                Some(serde_json::from_str(attributes_str)?)
-            }
-        } else {
-            None
-        };
+    Ok((key.to_string(), attributes))
+}
 
-        return Ok((key.as_str().to_string(), attributes));
-    }
+/// Prefix applied to each rendered line so snippets keep the indentation and blockquote or comment
+/// prefix of their target marker. The `indent` and `prefix` attributes override the marker's
+/// leading whitespace and the remainder of its prefix respectively.
+fn rendered_line_prefix(
+    marker_prefix: &str,
+    attributes: Option<&HashMap<String, Value>>,
+) -> SnippextResult<String> {
+    let prefix = marker_prefix.trim_start();
+    let indent = &marker_prefix[..marker_prefix.len() - prefix.len()];
+
+    let indent = match attributes.and_then(|a| a.get("indent")) {
+        None => indent.to_string(),
+        Some(Value::Number(n)) => {
+            n.as_u64()
+                .map(|n| " ".repeat(n as usize))
+                .ok_or(SnippextError::GeneralError(
+                    "indent must be a non-negative number".to_string(),
+                ))?
+        }
+        Some(Value::String(s)) => s.clone(),
+        Some(_) => {
+            return Err(SnippextError::GeneralError(
+                "indent must be a number or a string".to_string(),
+            ))
+        }
+    };
+
+    let prefix = match attributes.and_then(|a| a.get("prefix")) {
+        None => prefix,
+        Some(Value::String(s)) => s.as_str(),
+        Some(_) => {
+            return Err(SnippextError::GeneralError(
+                "prefix must be a string".to_string(),
+            ))
+        }
+    };
+
+    Ok(format!("{}{}", indent, prefix))
+}
 
-    Err(SnippextError::GeneralError(format!(
-        "could not extract snippet details from {}",
-        line
-    )))
+/// Result of splicing snippets into a target file
+#[derive(Default)]
+struct ProcessedTarget {
+    missing_snippets: Vec<MissingSnippet>,
+    /// Warnings logged once all targets are processed so they're reported in target order
+    warnings: Vec<String>,
+    /// Whether the target's content changed and was written
+    changed: bool,
 }
 
 fn process_target_file(
     target: &Path,
     snippets: &HashMap<String, Snippet>,
     settings: &SnippextSettings,
+    registry: &TemplateRegistry,
+    groups: &SnippetGroups,
     cache: &SnippetCommentCache,
-) -> SnippextResult<Vec<MissingSnippet>> {
+    index: &SnippextIndex,
+) -> SnippextResult<ProcessedTarget> {
     let mut new_file_lines = Vec::new();
     let mut updated = false;
     let mut in_current_snippet = None;
     let mut line_number = 0;
     let mut missing_snippets = Vec::new();
+    let mut warnings = Vec::new();
+    // snippets rendered into the target. Targets that include URL or file snippets are always
+    // processed given they aren't tracked by the index
+    let mut rendered_snippets = Vec::new();
+    let mut indexable = true;
     let extension = files::extension_from_path(target);
-    let snippet_comments = cache.get(extension);
+    let snippet_comments = cache.get(extension.clone());
 
-    let f = File::open(target)?;
-    let reader = BufReader::new(f);
-    for line in reader.lines() {
+    let content = fs::read(target)?;
+    if index.unchanged_target(target, &content, snippets) {
+        return Ok(ProcessedTarget::default());
+    }
+
+    if !snippet_comments.may_contain_snippets(&content) {
+        index.record_target(target, &content, rendered_snippets);
+        return Ok(ProcessedTarget::default());
+    }
+
+    let format = TextFormat::detect(&content).with_line_ending(settings.line_endings);
+    let mut code_blocks = CodeBlocks::new(&extension);
+    let markup = files::is_markup(&extension);
+    for line in TextFormat::strip_bom(&content).lines() {
         line_number += 1;
         let line = line?;
-        let current_line = line.trim();
+        let (marker_prefix, marker) = files::split_marker_prefix(&line, markup);
+        let current_line = marker.trim_end();
 
         if in_current_snippet.is_some() {
             if snippet_comments.is_line_end_snippet(current_line).is_some() {
@@ -877,6 +1688,7 @@ fn process_target_file(
         }
 
         new_file_lines.push(line.clone());
+        let in_code_block = code_blocks.contains(&line);
 
         let snippet_comment = snippet_comments.is_line_start_snippet(current_line);
         if snippet_comment.is_none() {
@@ -886,28 +1698,104 @@ fn process_target_file(
         let Ok((key, attributes)) =
             extract_id_and_attributes(current_line, snippet_comment.unwrap())
         else {
-            warn!(
-                "Failed to extract id/attributes from snippet. File {} line number {}",
-                target.to_string_lossy(),
-                line_number
-            );
+            if !in_code_block {
+                warn!(
+                    "Failed to extract id/attributes from snippet. File {} line number {}",
+                    target.to_string_lossy(),
+                    line_number
+                );
+            }
+            continue;
+        };
+
+        // markers within code blocks are usually documentation examples
+        if in_code_block
+            && !settings.code_block_markers
+            && !code_blocks::is_opted_in(attributes.as_ref())
+        {
             continue;
+        }
+
+        let group = match attributes
+            .as_ref()
+            .and_then(|a| a.get(SNIPPEXT_GROUP_ATTRIBUTE))
+        {
+            Some(Value::String(group)) => Some(group.clone()),
+            Some(Value::Bool(true)) => Some(key.clone()),
+            _ => None,
         };
 
-        let mut found = false;
-        if let Some(snippet) = find_snippet(snippets, &key) {
-            found = true;
-            let result = render_template(None, &snippet, settings, attributes)?;
+        let rendered = if let Some(group) = &group {
+            // new members can be added to a group without changing any indexed snippet
+            indexable = false;
+            groups
+                .get(group)
+                .map(|members| {
+                    render_group_template(
+                        registry,
+                        group,
+                        members,
+                        settings,
+                        Some(target),
+                        attributes.clone(),
+                    )
+                })
+                .transpose()?
+        } else {
+            match snippets.get(&key) {
+                Some(snippet) => rendered_snippets.push(snippet),
+                None => indexable = false,
+            }
+
+            if groups.has_multiple_languages(&key) {
+                warnings.push(format!(
+                    "Snippet {} in {} at line {} exists in multiple languages and only the last \
+                     one extracted is rendered. Use a group marker to render all of them",
+                    key,
+                    target.to_string_lossy(),
+                    line_number
+                ));
+            }
+
+            find_snippet(snippets, &key)
+                .map(|snippet| {
+                    render_template(
+                        registry,
+                        None,
+                        &snippet,
+                        settings,
+                        Some(target),
+                        attributes.clone(),
+                    )
+                })
+                .transpose()?
+        };
 
-            let result_lines: Vec<String> = result.lines().map(|s| s.to_string()).collect();
+        // group markers can omit the key, e.g. `snippet::start {"group": "connect"}`
+        let key = group.filter(|_| key.is_empty()).unwrap_or(key);
+        if let Some(result) = rendered {
+            let marker_prefix = if markup {
+                files::continuation_prefix(marker_prefix)
+            } else {
+                marker_prefix.to_string()
+            };
+            let line_prefix = rendered_line_prefix(&marker_prefix, attributes.as_ref())?;
+            let result_lines: Vec<String> = result
+                .lines()
+                .map(|s| {
+                    if s.is_empty() {
+                        line_prefix.trim_end().to_string()
+                    } else {
+                        format!("{}{}", line_prefix, s)
+                    }
+                })
+                .collect();
             new_file_lines.extend(result_lines);
             updated = true;
-            in_current_snippet = Some(key.clone());
-        }
-
-        if !found {
+            in_current_snippet = Some(key);
+        } else {
             missing_snippets.push(MissingSnippet {
-                key: key.clone(),
+                key,
                 line_number,
                 path: target.to_owned(),
             });
@@ -921,11 +1809,22 @@ fn process_target_file(
         )));
     }
 
+    let mut changed = false;
     if updated {
-        fs::write(target, new_file_lines.join("\n"))?;
+        let new_content = format.join(&new_file_lines);
+        changed = files::write_if_changed(target, new_content.as_bytes())?;
+        if indexable {
+            index.record_target(target, new_content.as_bytes(), rendered_snippets);
+        }
+    } else if indexable {
+        index.record_target(target, &content, rendered_snippets);
     }
 
-    Ok(missing_snippets)
+    Ok(ProcessedTarget {
+        missing_snippets,
+        warnings,
+        changed,
+    })
 }
 
 fn find_snippet(snippets: &HashMap<String, Snippet>, key: &String) -> Option<Snippet> {
@@ -949,6 +1848,7 @@ fn find_snippet(snippets: &HashMap<String, Snippet>, key: &String) -> Option<Sni
                         start_line: 1,
                         end_line: line_count,
                         source_link: Some(key.to_string()),
+                        line_numbers: (1..=line_count).collect(),
                     });
                 }
             }
@@ -970,6 +1870,7 @@ fn find_snippet(snippets: &HashMap<String, Snippet>, key: &String) -> Option<Sni
             start_line: 1,
             end_line: line_count,
             source_link: Some(key.to_string()),
+            line_numbers: (1..=line_count).collect(),
         });
     }
 
@@ -1004,7 +1905,8 @@ fn build_settings(opt: Args) -> SnippextResult<SnippextSettings> {
         .set_override_option(
             "selected_lines_include_ellipses",
             opt.selected_lines_include_ellipses,
-        )?;
+        )?
+        .set_override_option("jobs", opt.jobs.map(|jobs| jobs as u64))?;
 
     if !opt.targets.is_empty() {
         builder = builder.set_override("targets", opt.targets)?;
@@ -1032,6 +1934,14 @@ fn build_settings(opt: Args) -> SnippextResult<SnippextSettings> {
         builder = builder.set_override("enable_autodetect_language", false)?;
     }
 
+    if opt.no_ignore.is_some_and(|no_ignore| no_ignore) {
+        builder = builder.set_override("respect_ignore_files", false)?;
+    }
+
+    if opt.prune.is_some_and(|prune| prune) {
+        builder = builder.set_override("prune_output_dir", true)?;
+    }
+
     if let Some(template) = opt.templates {
         let templates_path = Path::new(template.as_str());
         if !templates_path.exists() {
@@ -1049,6 +1959,7 @@ fn build_settings(opt: Args) -> SnippextResult<SnippextSettings> {
         }
 
         let mut templates = HashMap::new();
+        let mut template_engines = HashMap::new();
         for entry in fs::read_dir(templates_path)? {
             let entry = entry?;
             let path = entry.path();
@@ -1066,12 +1977,18 @@ fn build_settings(opt: Args) -> SnippextResult<SnippextSettings> {
                 continue;
             };
 
-            templates.insert(file_name.to_string_lossy().to_string(), content);
+            let identifier = file_name.to_string_lossy().to_string();
+            if let Some(engine) = TemplateEngine::from_extension(&files::extension_from_path(&path))
+            {
+                template_engines.insert(identifier.clone(), engine);
+            }
+            templates.insert(identifier, content);
         }
 
         // might be a better way to do this but works for now
         let templates_json = serde_json::to_string(&json!({
-            "templates": templates
+            "templates": templates,
+            "template_engines": template_engines,
         }))?;
 
         builder = builder.add_source(config::File::from_str(
@@ -1088,20 +2005,25 @@ fn build_settings(opt: Args) -> SnippextResult<SnippextSettings> {
             opt.sources
         };
 
-        let source = SnippetSource::Git {
-            repository: repo_url,
-            branch: opt.repository_branch,
-            cone_patterns: opt.repository_cone_patterns,
-            files: source_files,
-        };
+        let source = SnippetSource::new_git(
+            repo_url,
+            opt.repository_branch,
+            opt.repository_cone_patterns,
+            source_files,
+        );
 
         snippet_sources.push(source);
     } else if !opt.sources.is_empty() {
-        snippet_sources.push(SnippetSource::Local { files: opt.sources });
+        snippet_sources.push(SnippetSource::Local {
+            files: opt.sources,
+            exclude: Vec::new(),
+            stdin_language: opt.stdin_language,
+            overrides: Default::default(),
+        });
     }
 
     for url_source in opt.url_sources {
-        snippet_sources.push(SnippetSource::Url { url: url_source });
+        snippet_sources.push(SnippetSource::new_url(url_source));
     }
 
     // might be a better way to do this but works for now
@@ -1120,10 +2042,12 @@ fn build_settings(opt: Args) -> SnippextResult<SnippextSettings> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::fs;
     use std::path::{Path, PathBuf};
 
     use indexmap::IndexMap;
+    use serde_json::json;
     use tempfile::tempdir;
     use tracing_test::traced_test;
 
@@ -1132,7 +2056,7 @@ mod tests {
     use crate::constants::{DEFAULT_START, DEFAULT_TEMPLATE_IDENTIFIER};
     use crate::error::SnippextError;
     use crate::settings::SnippextSettings;
-    use crate::types::{LinkFormat, SnippetSource};
+    use crate::types::{Dedent, LinkFormat, SnippetSource, TemplateEngine};
 
     #[test]
     fn verify_cli_args() {
@@ -1145,6 +2069,7 @@ mod tests {
             repository_branch: Some(String::from("main")),
             repository_cone_patterns: None,
             sources: vec![String::from("**/*.rs")],
+            stdin_language: None,
             url_sources: Vec::default(),
             output_dir: Some(String::from("./snippext/")),
             output_extension: Some(String::from("txt")),
@@ -1156,6 +2081,10 @@ mod tests {
             retain_nested_snippet_comments: None,
             disable_language_autodetect: None,
             selected_lines_include_ellipses: None,
+            jobs: Some(2),
+            force: false,
+            no_ignore: None,
+            prune: None,
         };
 
         let settings = super::build_settings(args).unwrap();
@@ -1163,6 +2092,7 @@ mod tests {
         assert_eq!("snippet::start", settings.start);
         assert_eq!("finish::", settings.end);
         assert_eq!(Some("txt".into()), settings.output_extension);
+        assert_eq!(Some(2), settings.jobs);
         assert_eq!(2, settings.templates.len());
 
         let default_template = settings.templates.get(DEFAULT_TEMPLATE_IDENTIFIER).unwrap();
@@ -1171,7 +2101,7 @@ mod tests {
         assert_eq!(Some(vec![String::from("README.md")]), settings.targets);
 
         assert_eq!(1, settings.sources.len());
-        let source = settings.sources.get(0).unwrap();
+        let source = settings.sources.first().unwrap();
         match source {
             SnippetSource::Git {
                 repository: url,
@@ -1209,6 +2139,7 @@ mod tests {
             output_extension: Some(String::from("txt")),
             targets: Vec::default(),
             sources: Vec::default(),
+            stdin_language: None,
             url_sources: Vec::default(),
             link_format: None,
             source_link_prefix: None,
@@ -1217,6 +2148,10 @@ mod tests {
             retain_nested_snippet_comments: None,
             disable_language_autodetect: None,
             selected_lines_include_ellipses: None,
+            jobs: None,
+            force: false,
+            no_ignore: None,
+            prune: None,
         };
 
         let settings = super::build_settings(opt).unwrap();
@@ -1227,7 +2162,53 @@ mod tests {
         );
         // cli arg overrides env
         assert_eq!(Some("txt".into()), settings.output_extension);
-        assert_eq!(true, settings.omit_source_links);
+        assert!(settings.omit_source_links);
+        assert_eq!(Dedent::Columns(2), settings.dedent);
+        assert_eq!(8, settings.tab_width);
+    }
+
+    #[test]
+    fn should_select_template_engine_by_extension() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("default.hbs"), "{{snippet}}").unwrap();
+        fs::write(dir.path().join("html.j2"), "<pre>{{ snippet }}</pre>").unwrap();
+
+        let opt = Args {
+            config: None,
+            start: None,
+            end: None,
+            templates: Some(dir.path().to_string_lossy().to_string()),
+            repository_url: None,
+            repository_branch: None,
+            repository_cone_patterns: None,
+            output_dir: None,
+            output_extension: None,
+            targets: Vec::default(),
+            sources: Vec::default(),
+            stdin_language: None,
+            url_sources: Vec::default(),
+            link_format: None,
+            source_link_prefix: None,
+            omit_source_links: None,
+            missing_snippets_behavior: None,
+            retain_nested_snippet_comments: None,
+            disable_language_autodetect: None,
+            selected_lines_include_ellipses: None,
+            jobs: None,
+            force: false,
+            no_ignore: None,
+            prune: None,
+        };
+
+        let settings = super::build_settings(opt).unwrap();
+        assert_eq!(
+            Some(&TemplateEngine::Handlebars),
+            settings.template_engines.get("default")
+        );
+        assert_eq!(
+            Some(&TemplateEngine::MiniJinja),
+            settings.template_engines.get("html")
+        );
     }
 
     // https://users.rust-lang.org/t/whats-the-rust-way-to-unit-test-for-an-error/23677/2
@@ -1237,9 +2218,7 @@ mod tests {
             start: String::from(""),
             end: String::from(""),
             templates: IndexMap::from([("".to_string(), "".to_string())]),
-            sources: vec![SnippetSource::Local {
-                files: vec![String::from("**")],
-            }],
+            sources: vec![SnippetSource::new_local(vec![String::from("**")])],
             output_dir: Some(String::from("./snippets/")),
             output_extension: Some(String::from("")),
             ..Default::default()
@@ -1272,9 +2251,7 @@ mod tests {
             start: String::from(""),
             end: String::from(""),
             templates: IndexMap::from([("default".to_string(), "".to_string())]),
-            sources: vec![SnippetSource::Local {
-                files: vec![String::from("**")],
-            }],
+            sources: vec![SnippetSource::new_local(vec![String::from("**")])],
             output_dir: Some(String::from("./snippets/")),
             ..Default::default()
         };
@@ -1296,13 +2273,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn templates_must_compile() {
+        let settings = SnippextSettings {
+            templates: IndexMap::from([
+                ("default".to_string(), "{{snippet}}".to_string()),
+                (
+                    "html".to_string(),
+                    "<pre>\n{{#if lang}}{{lang}}\n</pre>".to_string(),
+                ),
+            ]),
+            sources: vec![SnippetSource::new_local(vec![String::from("**")])],
+            output_dir: Some(String::from("./snippets/")),
+            ..Default::default()
+        };
+
+        let validation_result = super::extract(settings);
+        let error = validation_result.err().unwrap();
+        match error {
+            SnippextError::ValidationError(failures) => {
+                assert_eq!(1, failures.len());
+                assert!(
+                    failures[0].starts_with("templates[html] is not a valid template at line 3"),
+                    "{}",
+                    failures[0]
+                );
+            }
+            _ => {
+                panic!("invalid SnippextError");
+            }
+        }
+    }
+
     #[test]
     fn at_least_one_template_is_required() {
         let settings = SnippextSettings {
             templates: IndexMap::new(),
-            sources: vec![SnippetSource::Local {
-                files: vec![String::from("**")],
-            }],
+            sources: vec![SnippetSource::new_local(vec![String::from("**")])],
             output_dir: Some(String::from("./snippets/")),
             output_extension: Some(String::from("md")),
             ..Default::default()
@@ -1315,7 +2322,7 @@ mod tests {
                 assert_eq!(1, failures.len());
                 assert_eq!(
                     String::from("templates must not be empty"),
-                    failures.get(0).unwrap().to_string()
+                    failures.first().unwrap().to_string()
                 );
             }
             _ => {
@@ -1331,9 +2338,7 @@ mod tests {
                 ("first".to_string(), String::from("{{snippet}}")),
                 ("second".to_string(), String::from("{{snippet}}")),
             ]),
-            sources: vec![SnippetSource::Local {
-                files: vec![String::from("**")],
-            }],
+            sources: vec![SnippetSource::new_local(vec![String::from("**")])],
             output_dir: Some(String::from("./snippets/")),
             output_extension: Some(String::from("md")),
             ..Default::default()
@@ -1346,7 +2351,7 @@ mod tests {
                 assert_eq!(1, failures.len());
                 assert_eq!(
                     String::from("Must have one template named 'default'"),
-                    failures.get(0).unwrap().to_string()
+                    failures.first().unwrap().to_string()
                 );
             }
             _ => {
@@ -1375,7 +2380,7 @@ mod tests {
                 assert_eq!(1, failures.len());
                 assert_eq!(
                     String::from("sources must not be empty"),
-                    failures.get(0).unwrap().to_string()
+                    failures.first().unwrap().to_string()
                 );
             }
             _ => {
@@ -1391,7 +2396,7 @@ mod tests {
                 DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
                 String::from("{{snippet}}"),
             )]),
-            sources: vec![SnippetSource::Local { files: vec![] }],
+            sources: vec![SnippetSource::new_local(vec![])],
             output_dir: Some(String::from("./snippets/")),
             output_extension: Some(String::from("md")),
             ..Default::default()
@@ -1405,7 +2410,7 @@ mod tests {
                 assert_eq!(1, failures.len());
                 assert_eq!(
                     String::from("sources[0].files must not be empty"),
-                    failures.get(0).unwrap().to_string()
+                    failures.first().unwrap().to_string()
                 );
             }
             _ => {
@@ -1415,15 +2420,212 @@ mod tests {
     }
 
     #[test]
-    fn should_return_error_when_missing_snippets_behavior_is_fail_no_snippets() {
+    fn markdown_index_requires_snippets_index() {
+        let settings = SnippextSettings {
+            generate_markdown_index: true,
+            ..Default::default()
+        };
+
+        let error = super::extract(settings).unwrap_err();
+        match error {
+            SnippextError::ValidationError(failures) => {
+                assert_eq!(
+                    vec![String::from(
+                        "generate_markdown_index requires generate_snippets_index"
+                    )],
+                    failures
+                );
+            }
+            _ => {
+                panic!("invalid SnippextError");
+            }
+        }
+    }
+
+    #[test]
+    fn archive_sources_must_have_valid_files_globs() {
+        let settings = SnippextSettings {
+            templates: IndexMap::from([(
+                DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+                String::from("{{snippet}}"),
+            )]),
+            sources: vec![SnippetSource::Archive {
+                path_or_url: String::from("sdk-1.0.0.tar.gz"),
+                files: vec![String::from("src/**/*.rs"), String::from("src/[.rs")],
+                strip_prefix: None,
+                source_link_template: None,
+                overrides: Default::default(),
+            }],
+            output_dir: Some(String::from("./snippets/")),
+            output_extension: Some(String::from("md")),
+            ..Default::default()
+        };
+
+        let validation_result = super::extract(settings);
+        let error = validation_result.err().unwrap();
+
+        match error {
+            SnippextError::ValidationError(failures) => {
+                assert_eq!(1, failures.len());
+                assert!(failures
+                    .first()
+                    .unwrap()
+                    .starts_with("sources[0].files[1] is not a valid glob pattern."));
+            }
+            _ => {
+                panic!("invalid SnippextError");
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_extract_from_command_output() {
+        let dir = tempdir().unwrap();
+
+        let settings = SnippextSettings {
+            templates: IndexMap::from([(
+                DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+                String::from("```{{lang}}\n{{snippet}}```"),
+            )]),
+            sources: vec![SnippetSource::Command {
+                command: "cat".into(),
+                args: vec!["main.rs".into()],
+                cwd: Some("./tests/samples".into()),
+                language: Some("Rust".into()),
+                overrides: Default::default(),
+            }],
+            output_dir: Some(dir.path().to_string_lossy().to_string()),
+            output_extension: Some(String::from("md")),
+            ..Default::default()
+        };
+
+        super::extract(settings).unwrap();
+
+        let actual = fs::read_to_string(dir.path().join("cat/nested_default.md")).unwrap();
+        assert_eq!("```rust\nprintln!(\"printing...\")\n```", actual);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_decode_command_output_like_source_files() {
+        let dir = tempdir().unwrap();
+        let printf = |format: &str| SnippetSource::Command {
+            command: "printf".into(),
+            args: vec![format.into()],
+            cwd: None,
+            language: Some("rs".into()),
+            overrides: Default::default(),
+        };
+
         let settings = SnippextSettings {
             templates: IndexMap::from([(
                 DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
                 String::from("{{snippet}}"),
             )]),
-            sources: vec![SnippetSource::Local {
-                files: vec!["./tests/samples/no_snippets.rs".into()],
+            sources: vec![
+                printf("// snippet::start latin1\\n// caf\\351\\n// snippet::end\\n"),
+                printf("// snippet::start binary\\n\\000\\n// snippet::end\\n"),
+            ],
+            output_dir: Some(dir.path().to_string_lossy().to_string()),
+            output_extension: Some(String::from("md")),
+            fallback_encoding: Some(String::from("latin1")),
+            ..Default::default()
+        };
+
+        super::extract(settings).unwrap();
+
+        let actual = fs::read_to_string(dir.path().join("printf/latin1_default.md")).unwrap();
+        assert_eq!("// café\n", actual);
+        assert!(!dir.path().join("printf/binary_default.md").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[traced_test]
+    fn should_warn_when_marker_matches_snippets_in_multiple_languages() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("target.md");
+        fs::write(
+            &target,
+            "<!-- snippet::start connect -->\n<!-- snippet::end -->\n\n\
+             <!-- snippet::start main -->\n<!-- snippet::end -->\n",
+        )
+        .unwrap();
+
+        let printf = |format: &str, language: &str| SnippetSource::Command {
+            command: "printf".into(),
+            args: vec![format.into()],
+            cwd: None,
+            language: Some(language.into()),
+            overrides: Default::default(),
+        };
+
+        let settings = SnippextSettings {
+            templates: IndexMap::from([(
+                DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+                String::from("{{lang}}: {{snippet}}"),
+            )]),
+            sources: vec![
+                printf(
+                    "// snippet::start connect\\nconnect()\\n// snippet::end\\n",
+                    "rust",
+                ),
+                printf(
+                    "# snippet::start connect\\nconnect()\\n# snippet::end\\n\\
+                     # snippet::start main\\nmain()\\n# snippet::end\\n",
+                    "python",
+                ),
+            ],
+            output_dir: None,
+            targets: Some(vec![target.to_string_lossy().to_string()]),
+            ..Default::default()
+        };
+
+        super::extract(settings).unwrap();
+
+        assert!(fs::read_to_string(&target)
+            .unwrap()
+            .contains("python: connect()"));
+        assert!(logs_contain(
+            "Snippet connect in {} at line 1 exists in multiple languages"
+                .replace("{}", &target.to_string_lossy())
+                .as_str()
+        ));
+        assert!(!logs_contain("Snippet main in"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_return_error_when_command_fails() {
+        let dir = tempdir().unwrap();
+
+        let settings = SnippextSettings {
+            sources: vec![SnippetSource::Command {
+                command: "cat".into(),
+                args: vec!["does_not_exist.rs".into()],
+                cwd: None,
+                language: None,
+                overrides: Default::default(),
             }],
+            output_dir: Some(dir.path().to_string_lossy().to_string()),
+            ..Default::default()
+        };
+
+        let error = super::extract(settings).unwrap_err();
+        assert!(error.to_string().starts_with("Command `cat` failed"));
+    }
+
+    #[test]
+    fn should_return_error_when_missing_snippets_behavior_is_fail_no_snippets() {
+        let settings = SnippextSettings {
+            templates: IndexMap::from([(
+                DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+                String::from("{{snippet}}"),
+            )]),
+            sources: vec![SnippetSource::new_local(vec![
+                "./tests/samples/no_snippets.rs".into(),
+            ])],
             output_dir: Some(String::from("./snippets/")),
             output_extension: Some(String::from("md")),
             targets: Some(vec!["./tests/targets/specify_template.md".into()]),
@@ -1438,7 +2640,7 @@ mod tests {
             SnippextError::MissingSnippetsError(missing_snippets) => {
                 assert_eq!(1, missing_snippets.len());
 
-                let missing = missing_snippets.get(0).unwrap();
+                let missing = missing_snippets.first().unwrap();
                 assert_eq!("main", missing.key);
                 assert_eq!(
                     "tests/targets/specify_template.md",
@@ -1463,9 +2665,9 @@ mod tests {
                 DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
                 String::from("{{snippet}}"),
             )]),
-            sources: vec![SnippetSource::Local {
-                files: vec!["./tests/samples/main.rs".into()],
-            }],
+            sources: vec![SnippetSource::new_local(vec![
+                "./tests/samples/main.rs".into()
+            ])],
             output_dir: Some(String::from("./snippets/")),
             output_extension: Some(String::from("md")),
             targets: Some(vec![target.to_string_lossy().to_string()]),
@@ -1480,7 +2682,7 @@ mod tests {
             SnippextError::MissingSnippetsError(missing_snippets) => {
                 assert_eq!(1, missing_snippets.len());
 
-                let missing = missing_snippets.get(0).unwrap();
+                let missing = missing_snippets.first().unwrap();
                 assert_eq!("fn_1", missing.key);
                 assert_eq!(target.to_string_lossy(), missing.path.to_string_lossy());
                 assert_eq!(6, missing.line_number);
@@ -1499,9 +2701,9 @@ mod tests {
                 DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
                 String::from("{{snippet}}"),
             )]),
-            sources: vec![SnippetSource::Local {
-                files: vec!["./tests/samples/no_snippets.rs".into()],
-            }],
+            sources: vec![SnippetSource::new_local(vec![
+                "./tests/samples/no_snippets.rs".into(),
+            ])],
             output_dir: Some(String::from("./snippets/")),
             output_extension: Some(String::from("md")),
             targets: Some(vec!["./tests/targets/specify_template.md".into()]),
@@ -1528,7 +2730,7 @@ mod tests {
                 DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
                 String::from("{{snippet}}"),
             )]),
-            sources: vec![SnippetSource::Url { url: "https://gist.githubusercontent.com/seancarroll/94629074d8cb36e9f5a0bc47b72ba6a5/raw/2b9d5db6482c7ff90a0cf3689d2a36b99e77d189/snippext_example.rs".into() }],
+            sources: vec![SnippetSource::new_url("https://gist.githubusercontent.com/seancarroll/94629074d8cb36e9f5a0bc47b72ba6a5/raw/2b9d5db6482c7ff90a0cf3689d2a36b99e77d189/snippext_example.rs".into())],
             targets: Some(vec![target.to_string_lossy().to_string()]),
             ..Default::default()
         };
@@ -1561,9 +2763,7 @@ some content
                 DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
                 String::from("{{snippet}}"),
             )]),
-            sources: vec![SnippetSource::Local {
-                files: vec!["./tests/samples/**".into()],
-            }],
+            sources: vec![SnippetSource::new_local(vec!["./tests/samples/**".into()])],
             targets: Some(vec![target.to_string_lossy().to_string()]),
             ..Default::default()
         };
@@ -1594,7 +2794,8 @@ AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
 LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
 OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
-<!-- snippet::end -->"#;
+<!-- snippet::end -->
+"#;
         assert_eq!(expected, actual);
     }
 
@@ -1609,9 +2810,7 @@ SOFTWARE.
                 DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
                 String::from("{{snippet}}"),
             )]),
-            sources: vec![SnippetSource::Local {
-                files: vec!["./tests/samples/**".into()],
-            }],
+            sources: vec![SnippetSource::new_local(vec!["./tests/samples/**".into()])],
             targets: Some(vec![target.to_string_lossy().to_string()]),
             ..Default::default()
         };
@@ -1642,14 +2841,14 @@ AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
 LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
 OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
-<!-- snippet::end -->"#;
+<!-- snippet::end -->
+"#;
         assert_eq!(expected, actual);
     }
 
     #[test]
     fn local_source_link_without_prefix() {
-        let source_link =
-            SourceLink::new_local("src/main.rs".into(), None, Some(LinkFormat::GitHub));
+        let source_link = SourceLink::new_local("src/main.rs", None, Some(LinkFormat::GitHub));
 
         let source_link_str = source_link.append_lines(1, 10);
 
@@ -1658,7 +2857,7 @@ SOFTWARE.
 
     #[test]
     fn local_source_without_link_format_should_return_path() {
-        let source_link = SourceLink::new_local("src/main.rs".into(), None, None);
+        let source_link = SourceLink::new_local("src/main.rs", None, None);
 
         let source_link_str = source_link.append_lines(1, 10);
         assert_eq!("src/main.rs", source_link_str);
@@ -1667,7 +2866,7 @@ SOFTWARE.
     #[test]
     fn local_source_link_with_prefix() {
         let source_link = SourceLink::new_local(
-            "src/main.rs".into(),
+            "src/main.rs",
             Some("https://github.com/doctavious/snippext/blob/main/"),
             Some(LinkFormat::GitHub),
         );
@@ -1678,4 +2877,17 @@ SOFTWARE.
             source_link_str
         );
     }
+
+    #[test]
+    fn should_reject_negative_indent() {
+        let attributes = HashMap::from([("indent".to_string(), json!(-2))]);
+        let error = super::rendered_line_prefix("", Some(&attributes)).unwrap_err();
+        assert_eq!("indent must be a non-negative number", error.to_string());
+
+        let attributes = HashMap::from([("indent".to_string(), json!(0))]);
+        assert_eq!(
+            "> ",
+            super::rendered_line_prefix("  > ", Some(&attributes)).unwrap()
+        );
+    }
 }
@@ -7,11 +7,12 @@ use inquire::{Confirm, CustomUserError, Editor, Select, Text};
 use tracing::warn;
 
 use crate::constants::{
-    DEFAULT_END, DEFAULT_GIT_BRANCH, DEFAULT_OUTPUT_FILE_EXTENSION, DEFAULT_SNIPPEXT_CONFIG,
-    DEFAULT_SOURCE_FILES, DEFAULT_START, DEFAULT_TEMPLATE, DEFAULT_TEMPLATE_IDENTIFIER,
+    DEFAULT_END, DEFAULT_GIT_BRANCH, DEFAULT_INDEX_FILE, DEFAULT_MAX_FILE_SIZE,
+    DEFAULT_OUTPUT_FILE_EXTENSION, DEFAULT_SNIPPEXT_CONFIG, DEFAULT_SOURCE_FILES, DEFAULT_START,
+    DEFAULT_TAB_WIDTH, DEFAULT_TEMPLATE, DEFAULT_TEMPLATE_IDENTIFIER,
 };
 use crate::error::SnippextError;
-use crate::types::{LinkFormat, MissingSnippetsBehavior, SnippetSource};
+use crate::types::{Dedent, LinkFormat, MissingSnippetsBehavior, SnippetSource, TemplateEngine};
 use crate::{SnippextResult, SnippextSettings};
 
 /// Initialize a Snippext configuration file which contains options for extracting snippets
@@ -103,9 +104,93 @@ fn init_settings_from_prompt() -> SnippextResult<SnippextSettings> {
     let mut sources: Vec<SnippetSource> = Vec::new();
     loop {
         // TODO: get variations from SnippetSource
-        let source_type = Select::new("Type of source?", vec!["local", "git", "url"]).prompt()?;
+        let source_type = Select::new(
+            "Type of source?",
+            vec!["local", "git", "url", "archive", "command"],
+        )
+        .prompt()?;
 
         match source_type {
+            "archive" => {
+                let path_or_url = Text::new("Archive:")
+                    .with_validator(NotEmptyValidator::default())
+                    .with_help_message(
+                        "Path or URL of a .zip, .tar, .tar.gz or .tgz archive to extract snippets from",
+                    )
+                    .prompt()?;
+
+                let source_files_prompt = Text::new("Source files:")
+                    .with_default(DEFAULT_SOURCE_FILES)
+                    .with_help_message(
+                        "List of glob patterns, separated by space, matched against archive \
+                        entries to look for snippets.",
+                    )
+                    .prompt()?;
+
+                let source_files = source_files_prompt
+                    .split(' ')
+                    .filter(|x| !x.is_empty())
+                    .map(|s| s.to_string())
+                    .collect();
+
+                let strip_prefix = Text::new("Strip prefix:")
+                    .with_help_message(
+                        "Leading path to remove from archive entries. Press escape to skip",
+                    )
+                    .prompt_skippable()?
+                    .filter(|p| !p.trim().is_empty());
+
+                let source_link_template = Text::new("Source link template:")
+                    .with_help_message(
+                        "Handlebars template used to build source links with `path`, \
+                        `start_line` and `end_line` available. Press escape to skip",
+                    )
+                    .prompt_skippable()?
+                    .filter(|t| !t.trim().is_empty());
+
+                sources.push(SnippetSource::Archive {
+                    path_or_url,
+                    files: source_files,
+                    strip_prefix,
+                    source_link_template,
+                    overrides: Default::default(),
+                });
+            }
+            "command" => {
+                let command = Text::new("Command:")
+                    .with_validator(NotEmptyValidator::default())
+                    .with_help_message("Command whose output snippets will be extracted from")
+                    .prompt()?;
+
+                let args = Text::new("Arguments:")
+                    .with_help_message("Arguments, separated by space, passed to the command")
+                    .prompt()?
+                    .split(' ')
+                    .filter(|x| !x.is_empty())
+                    .map(|s| s.to_string())
+                    .collect();
+
+                let cwd = Text::new("Working directory:")
+                    .with_help_message("Directory to run the command from. Press escape to skip")
+                    .prompt_skippable()?
+                    .filter(|c| !c.trim().is_empty());
+
+                let language = Text::new("Language:")
+                    .with_help_message(
+                        "Language, name or file extension, of the command output. \
+                        Press escape to skip",
+                    )
+                    .prompt_skippable()?
+                    .filter(|l| !l.trim().is_empty());
+
+                sources.push(SnippetSource::Command {
+                    command,
+                    args,
+                    cwd,
+                    language,
+                    overrides: Default::default(),
+                });
+            }
             "git" => {
                 let repo = Text::new("Repository:")
                     .with_validator(NotEmptyValidator::default())
@@ -141,12 +226,12 @@ fn init_settings_from_prompt() -> SnippextResult<SnippextSettings> {
                     .map(|s| s.to_string())
                     .collect();
 
-                sources.push(SnippetSource::Git {
-                    repository: repo,
-                    branch: Some(repository_branch),
+                sources.push(SnippetSource::new_git(
+                    repo,
+                    Some(repository_branch),
                     cone_patterns,
-                    files: source_files,
-                });
+                    source_files,
+                ));
             }
             "local" => {
                 let source_files_prompt = Text::new("Source files:")
@@ -163,16 +248,14 @@ fn init_settings_from_prompt() -> SnippextResult<SnippextSettings> {
                     .map(|s| s.to_string())
                     .collect();
 
-                sources.push(SnippetSource::Local {
-                    files: source_files,
-                });
+                sources.push(SnippetSource::new_local(source_files));
             }
             "url" => {
                 let url = Text::new("URL:")
                     .with_validator(NotEmptyValidator::default())
                     .with_help_message("URL to content that should be included as snippets")
                     .prompt()?;
-                sources.push(SnippetSource::Url { url });
+                sources.push(SnippetSource::new_url(url));
             }
             _ => {
                 warn!("Invalid source type {}", source_type);
@@ -276,10 +359,17 @@ fn init_settings_from_prompt() -> SnippextResult<SnippextSettings> {
         start,
         end,
         output_extension,
+        output_extensions: IndexMap::new(),
+        output_path_template: None,
         templates,
+        template_engine: TemplateEngine::default(),
+        template_engines: IndexMap::new(),
         sources,
         output_dir,
         targets: Some(targets.split(',').map(|t| t.to_string()).collect()),
+        target_templates: IndexMap::new(),
+        group_separator: None,
+        group_template: None,
         link_format,
         source_link_prefix,
         omit_source_links,
@@ -291,6 +381,21 @@ fn init_settings_from_prompt() -> SnippextResult<SnippextSettings> {
         retain_nested_snippet_comments,
         enable_autodetect_language: !disable_language_autodetect,
         selected_lines_include_ellipses,
+        line_numbers: false,
+        dedent: Dedent::default(),
+        tab_width: DEFAULT_TAB_WIDTH,
+        expand_tabs: false,
+        jobs: None,
+        index_file: Some(String::from(DEFAULT_INDEX_FILE)),
+        respect_ignore_files: true,
+        max_file_size: Some(DEFAULT_MAX_FILE_SIZE),
+        fallback_encoding: None,
+        line_endings: None,
+        code_block_markers: false,
+        prune_output_dir: false,
+        generate_snippets_index: false,
+        generate_markdown_index: false,
+        strict_templates: false,
     })
 }
 
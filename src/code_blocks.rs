@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::constants::SNIPPEXT_CODE_BLOCK_ATTRIBUTE;
+use crate::files;
+
+/// reStructuredText directives whose content is a literal block
+const RESTRUCTUREDTEXT_CODE_DIRECTIVES: [&str; 4] =
+    ["code", "code-block", "parsed-literal", "sourcecode"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Markup {
+    AsciiDoc,
+    Markdown,
+    ReStructuredText,
+}
+
+/// Tracks whether lines of Markdown, AsciiDoc and reStructuredText target files are within fenced,
+/// delimited or literal blocks so snippet markers shown as examples in documentation aren't
+/// processed. Lines must be provided in order via [`CodeBlocks::contains`].
+pub(crate) struct CodeBlocks {
+    markup: Option<Markup>,
+    /// Delimiter that closes the current fenced or delimited block
+    fence: Option<String>,
+    /// Indentation of the line introducing the current reStructuredText literal block
+    literal_indent: Option<usize>,
+}
+
+impl CodeBlocks {
+    pub fn new(extension: &str) -> Self {
+        let markup = match extension {
+            "adoc" => Some(Markup::AsciiDoc),
+            "md" => Some(Markup::Markdown),
+            "rst" => Some(Markup::ReStructuredText),
+            _ => None,
+        };
+
+        Self {
+            markup,
+            fence: None,
+            literal_indent: None,
+        }
+    }
+
+    /// Determine whether `line`, the next line of the file, is part of a code block. Fence and
+    /// delimiter lines are considered part of the block.
+    pub fn contains(&mut self, line: &str) -> bool {
+        match self.markup {
+            Some(Markup::AsciiDoc) => self.delimited_block(line),
+            Some(Markup::Markdown) => self.fenced_block(line),
+            Some(Markup::ReStructuredText) => self.literal_block(line),
+            None => false,
+        }
+    }
+
+    fn fenced_block(&mut self, line: &str) -> bool {
+        // fences can be nested in blockquotes and list items
+        let (_, line) = files::split_marker_prefix(line, true);
+        let line = line.trim_end();
+
+        if let Some(fence) = &self.fence {
+            let closing = line.trim_start_matches(fence.chars().next().unwrap_or('`'));
+            if line.len() - closing.len() >= fence.len() && closing.is_empty() {
+                self.fence = None;
+            }
+            return true;
+        }
+
+        for fence_char in ['`', '~'] {
+            let info = line.trim_start_matches(fence_char);
+            let length = line.len() - info.len();
+            if length >= 3 && !(fence_char == '`' && info.contains('`')) {
+                self.fence = Some(fence_char.to_string().repeat(length));
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn delimited_block(&mut self, line: &str) -> bool {
+        let line = line.trim_end();
+
+        if let Some(fence) = &self.fence {
+            if line == fence {
+                self.fence = None;
+            }
+            return true;
+        }
+
+        if line.starts_with("```") {
+            self.fence = Some(String::from("```"));
+            return true;
+        }
+
+        for delimiter in ['-', '.'] {
+            if line.len() >= 4 && line.chars().all(|c| c == delimiter) {
+                self.fence = Some(line.to_string());
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn literal_block(&mut self, line: &str) -> bool {
+        if line.trim().is_empty() {
+            return self.literal_indent.is_some();
+        }
+
+        let indent = line.len() - line.trim_start().len();
+        if let Some(literal_indent) = self.literal_indent {
+            if indent > literal_indent {
+                return true;
+            }
+            self.literal_indent = None;
+        }
+
+        let trimmed = line.trim();
+        let introduces_literal_block = match trimmed.strip_prefix(".. ") {
+            Some(directive) => directive
+                .split_once("::")
+                .is_some_and(|(name, _)| RESTRUCTUREDTEXT_CODE_DIRECTIVES.contains(&name.trim())),
+            None => trimmed.ends_with("::"),
+        };
+
+        if introduces_literal_block {
+            self.literal_indent = Some(indent);
+        }
+
+        false
+    }
+}
+
+/// Whether a marker's attributes opt it in to being processed within a code block
+pub(crate) fn is_opted_in(attributes: Option<&HashMap<String, Value>>) -> bool {
+    attributes
+        .and_then(|a| a.get(SNIPPEXT_CODE_BLOCK_ATTRIBUTE))
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CodeBlocks;
+
+    fn contained(extension: &str, content: &str) -> Vec<bool> {
+        let mut code_blocks = CodeBlocks::new(extension);
+        content.lines().map(|l| code_blocks.contains(l)).collect()
+    }
+
+    #[test]
+    fn should_track_markdown_fences() {
+        let content = "<!-- a -->\n````md\n```rust\n<!-- b -->\n```\n````\n> ~~~\n> <!-- c -->\n> ~~~\n<!-- d -->";
+        assert_eq!(
+            vec![false, true, true, true, true, true, true, true, true, false],
+            contained("md", content)
+        );
+    }
+
+    #[test]
+    fn should_track_asciidoc_delimited_blocks() {
+        let content = "// a\n[source,adoc]\n----\n// b\n----\n// c";
+        assert_eq!(
+            vec![false, false, true, true, true, false],
+            contained("adoc", content)
+        );
+    }
+
+    #[test]
+    fn should_track_restructuredtext_literal_blocks() {
+        let content = ".. a\n\n.. code-block:: rst\n\n   .. b\n\n.. note::\n\n   .. c\n\nExample::\n\n   .. d\n.. e";
+        assert_eq!(
+            vec![
+                false, false, false, true, true, true, false, false, false, false, false, true,
+                true, false
+            ],
+            contained("rst", content)
+        );
+    }
+}
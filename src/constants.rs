@@ -12,7 +12,30 @@ pub const DEFAULT_TEMPLATE: &str = r#"```{{lang}}
 "#;
 pub const DEFAULT_OUTPUT_FILE_EXTENSION: &str = "md";
 pub const DEFAULT_SOURCE_FILES: &str = "**";
+/// Local source file value that reads source content from stdin
+pub const STDIN_SOURCE: &str = "-";
 pub const DEFAULT_OUTPUT_DIR: &str = "./generated-snippets/";
+pub const DEFAULT_INDEX_FILE: &str = ".snippext/index.json";
+/// Manifest, written to the output directory, recording the files Snippext generated
+pub const OUTPUT_MANIFEST_FILE: &str = ".snippext-manifest.json";
+/// Index of extracted snippets, written to the output directory when enabled
+pub const SNIPPETS_INDEX_FILE: &str = "snippets.json";
+/// Markdown index of extracted snippets, written to the output directory when enabled
+pub const SNIPPETS_MARKDOWN_INDEX_FILE: &str = "index.md";
+/// Source files larger than 10 MiB are skipped by default
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+/// Columns a tab advances to when measuring indentation
+pub const DEFAULT_TAB_WIDTH: usize = 4;
 pub const SNIPPEXT_TEMPLATE_ATTRIBUTE: &str = "template";
+/// Target marker attribute that opts markers within code blocks in to being processed
+pub const SNIPPEXT_CODE_BLOCK_ATTRIBUTE: &str = "code_block";
 pub const DEFAULT_TEMPLATE_IDENTIFIER: &str = "default";
+/// Templates whose identifier starts with this prefix are only available as partials
+pub const PARTIAL_TEMPLATE_PREFIX: &str = "_";
+/// Prefix referring to a built-in template preset, e.g. `preset:asciidoc`
+pub const TEMPLATE_PRESET_PREFIX: &str = "preset:";
+/// Template used to render snippet groups when `group_template` isn't configured
+pub const DEFAULT_GROUP_TEMPLATE: &str = "preset:markdown-group";
+/// Source snippet and target marker attribute naming a snippet group
+pub const SNIPPEXT_GROUP_ATTRIBUTE: &str = "group";
 pub const DEFAULT_GIT_BRANCH: &str = "main";
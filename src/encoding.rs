@@ -0,0 +1,142 @@
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use encoding_rs::Encoding;
+use tracing::debug;
+
+/// Number of leading bytes inspected when determining if a file is binary. Same heuristic git uses.
+const BINARY_DETECTION_LENGTH: usize = 8000;
+
+/// Why a source file was skipped rather than having snippets extracted from it
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum SkipReason {
+    Binary,
+    TooLarge { size: u64, max_file_size: u64 },
+    Undecodable,
+}
+
+impl Display for SkipReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::Binary => write!(f, "binary file"),
+            SkipReason::TooLarge {
+                size,
+                max_file_size,
+            } => write!(
+                f,
+                "file size of {} bytes exceeds max_file_size of {} bytes",
+                size, max_file_size
+            ),
+            SkipReason::Undecodable => write!(f, "file is not valid UTF-8 or UTF-16"),
+        }
+    }
+}
+
+/// Source files skipped during extraction. Files are skipped from multiple threads and reported
+/// together once extraction completes.
+#[derive(Debug, Default)]
+pub(crate) struct SkippedFiles {
+    files: Mutex<Vec<(PathBuf, SkipReason)>>,
+}
+
+impl SkippedFiles {
+    pub fn skip(&self, path: &Path, reason: SkipReason) {
+        self.files
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push((path.to_path_buf(), reason));
+    }
+
+    /// Log a summary of all skipped files
+    pub fn report(self) {
+        let mut files = self.files.into_inner().unwrap_or_else(|e| e.into_inner());
+        if files.is_empty() {
+            return;
+        }
+
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        debug!("skipped {} source files", files.len());
+        for (path, reason) in files {
+            debug!("skipped {:?}: {}", path, reason);
+        }
+    }
+}
+
+/// Look up an encoding by its WHATWG label, e.g. `latin1` or `windows-1252`
+pub(crate) fn encoding_for_label(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+}
+
+/// Decode source file content to text.
+///
+/// Content starting with a UTF-8 or UTF-16 byte order mark is decoded with that encoding and the
+/// BOM removed. Otherwise content containing a NUL byte is considered binary. Content that isn't
+/// valid UTF-8 is decoded with the `fallback` encoding when provided.
+pub(crate) fn decode<'a>(
+    content: &'a [u8],
+    fallback: Option<&'static Encoding>,
+) -> Result<Cow<'a, str>, SkipReason> {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(content) {
+        return decode_with(encoding, &content[bom_length..]);
+    }
+
+    let head = &content[..content.len().min(BINARY_DETECTION_LENGTH)];
+    if memchr::memchr(0, head).is_some() {
+        return Err(SkipReason::Binary);
+    }
+
+    match std::str::from_utf8(content) {
+        Ok(text) => Ok(Cow::Borrowed(text)),
+        Err(_) => match fallback {
+            Some(encoding) => decode_with(encoding, content),
+            None => Err(SkipReason::Undecodable),
+        },
+    }
+}
+
+fn decode_with<'a>(
+    encoding: &'static Encoding,
+    content: &'a [u8],
+) -> Result<Cow<'a, str>, SkipReason> {
+    let (text, had_errors) = encoding.decode_without_bom_handling(content);
+    if had_errors {
+        Err(SkipReason::Undecodable)
+    } else {
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encoding_for_label, SkipReason};
+
+    #[test]
+    fn should_decode_byte_order_marks() {
+        let mut utf16 = vec![0xFF, 0xFE];
+        for unit in "// snippet::start main".encode_utf16() {
+            utf16.extend(unit.to_le_bytes());
+        }
+        assert_eq!("// snippet::start main", decode(&utf16, None).unwrap());
+
+        let utf8 = b"\xEF\xBB\xBF// snippet::start main";
+        assert_eq!("// snippet::start main", decode(utf8, None).unwrap());
+    }
+
+    #[test]
+    fn should_detect_binary_content() {
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR";
+        assert_eq!(Err(SkipReason::Binary), decode(png, None));
+    }
+
+    #[test]
+    fn should_decode_legacy_encodings_with_fallback() {
+        let latin1 = b"// caf\xE9";
+        assert_eq!(Err(SkipReason::Undecodable), decode(latin1, None));
+        assert_eq!(
+            "// café",
+            decode(latin1, encoding_for_label("latin1")).unwrap()
+        );
+    }
+}
@@ -33,6 +33,9 @@ pub enum SnippextError {
     #[error("Template render error: `{0}`")]
     TemplateRenderError(#[from] handlebars::RenderError),
 
+    #[error("Template render error: `{0}`")]
+    MiniJinjaError(#[from] minijinja::Error),
+
     #[error("Template not found: `{0}`")]
     TemplateNotFound(String),
 
@@ -1,5 +1,14 @@
+use std::cmp::Ordering;
+use std::fs;
+use std::io::{ErrorKind, Write};
 use std::path::Path;
 
+use memchr::memmem::Finder;
+use regex::Regex;
+
+use crate::types::LineEnding;
+use crate::SnippextResult;
+
 pub type CommentLexicalTokens = (&'static str, Option<&'static str>);
 
 pub const HTML_COMMENT: CommentLexicalTokens = ("<!--", Some("-->"));
@@ -12,28 +21,121 @@ pub const VB_COMMENT: CommentLexicalTokens = ("'", None);
 
 pub struct SnippextComment {
     pub start: String,
-    pub start_close: Option<String>,
     pub end: String,
+    /// Captures the snippet `key` and `attributes` from a start line
+    start_regex: Regex,
+}
+
+impl SnippextComment {
+    pub fn new(start: String, start_close: Option<String>, end: String) -> Self {
+        let regex_close = start_close
+            .as_ref()
+            .map(|s| format!("({}|$)", regex::escape(s)))
+            .unwrap_or("$".to_string());
+
+        let start_regex = Regex::new(
+            format!(
+                "{}[ ]*(?P<key>[\\S]*)(?P<attributes>.*?){}",
+                regex::escape(start.as_str()),
+                regex_close
+            )
+            .as_str(),
+        )
+        .expect("snippet comment regex must be valid");
+
+        Self {
+            start,
+            end,
+            start_regex,
+        }
+    }
+
+    /// Capture the snippet key and attributes, if any, from a snippet start line
+    pub fn captures<'l>(&self, line: &'l str) -> Option<(&'l str, Option<&'l str>)> {
+        let captures = self.start_regex.captures(line)?;
+        let key = captures.name("key")?.as_str();
+        let attributes = captures
+            .name("attributes")
+            .map(|a| a.as_str().trim())
+            .filter(|a| !a.is_empty());
+
+        Some((key, attributes))
+    }
 }
 
+/// Snippet comment matchers for a file extension. Built once per extension and shared across
+/// files so that marker detection doesn't allocate or compile anything per line.
 pub struct SnippextComments {
     comments: Vec<SnippextComment>,
+    /// Markers, without comment tokens, at least one of which must be present in content that
+    /// contains snippets
+    markers: Vec<Finder<'static>>,
+    /// First bytes of all start comments, used to cheaply reject lines
+    start_bytes: [bool; 256],
+    /// First bytes of all end comments, used to cheaply reject lines
+    end_bytes: [bool; 256],
 }
 
 impl SnippextComments {
     pub fn new(extension: &str, start: &str, end: &str) -> Self {
+        let comments = get_snippet_comments(extension, start, end);
+
+        let mut markers = vec![Finder::new(start).into_owned()];
+        let mut start_bytes = [false; 256];
+        let mut end_bytes = [false; 256];
+        for comment in &comments {
+            if !comment.start.contains(start) {
+                markers.push(Finder::new(comment.start.as_str()).into_owned());
+            }
+
+            if let Some(b) = comment.start.bytes().next() {
+                start_bytes[b as usize] = true;
+            }
+
+            if let Some(b) = comment.end.bytes().next() {
+                end_bytes[b as usize] = true;
+            }
+        }
+
         Self {
-            comments: get_snippet_comments(extension, start, end),
+            comments,
+            markers,
+            start_bytes,
+            end_bytes,
         }
     }
 
+    /// Fast check of whether content could contain any snippets. Content without any snippet
+    /// markers can be skipped without iterating over its lines.
+    pub fn may_contain_snippets(&self, content: &[u8]) -> bool {
+        self.markers
+            .iter()
+            .any(|marker| marker.find(content).is_some())
+    }
+
     pub fn is_line_start_snippet(&self, line: &str) -> Option<&SnippextComment> {
+        if !line
+            .bytes()
+            .next()
+            .is_some_and(|b| self.start_bytes[b as usize])
+        {
+            return None;
+        }
+
         self.comments
             .iter()
             .find(|&comment| line.starts_with(comment.start.as_str()))
     }
 
     pub fn is_line_end_snippet(&self, line: &str) -> Option<&SnippextComment> {
+        if !line
+            .bytes()
+            .next()
+            .is_some_and(|b| self.end_bytes[b as usize])
+        {
+            return None;
+        }
+
         self.comments
             .iter()
             .find(|&comment| line.starts_with(comment.end.as_str()))
@@ -44,32 +146,32 @@ pub fn get_snippet_comments(extension: &str, start: &str, end: &str) -> Vec<Snip
     let mut snippet_comments = Vec::new();
     for comment in file_comments(extension) {
         let start_close = comment.1.map(str::to_string);
-        snippet_comments.push(SnippextComment {
-            start: format!("{}{}", comment.0, start),
-            start_close: start_close.clone(),
-            end: format!("{}{}", comment.0, end),
-        });
-        snippet_comments.push(SnippextComment {
-            start: format!("{} {}", comment.0, start),
-            start_close: start_close.clone(),
-            end: format!("{} {}", comment.0, end),
-        });
+        snippet_comments.push(SnippextComment::new(
+            format!("{}{}", comment.0, start),
+            start_close.clone(),
+            format!("{}{}", comment.0, end),
+        ));
+        snippet_comments.push(SnippextComment::new(
+            format!("{} {}", comment.0, start),
+            start_close.clone(),
+            format!("{} {}", comment.0, end),
+        ));
     }
 
     if extension == "cs" {
-        snippet_comments.push(SnippextComment {
-            start: "#region".into(),
-            start_close: None,
-            end: "#endregion".into(),
-        });
+        snippet_comments.push(SnippextComment::new(
+            "#region".into(),
+            None,
+            "#endregion".into(),
+        ));
     }
 
     if extension == "vb" {
-        snippet_comments.push(SnippextComment {
-            start: "#Region".into(),
-            start_close: None,
-            end: "#End Region".into(),
-        });
+        snippet_comments.push(SnippextComment::new(
+            "#Region".into(),
+            None,
+            "#End Region".into(),
+        ));
     }
 
     snippet_comments
@@ -124,6 +226,35 @@ pub fn file_comments(extension: &str) -> Vec<CommentLexicalTokens> {
     }
 }
 
+/// File extension used to determine comments for a language name, e.g. `rust`. Values that are not
+/// a known language name are treated as an extension.
+pub fn extension_from_language(language: &str) -> String {
+    let language = language.to_ascii_lowercase();
+    let extension = match language.as_str() {
+        "asciidoc" => "adoc",
+        "bash" | "shell" => "sh",
+        "c#" | "csharp" => "cs",
+        "c++" => "cpp",
+        "elixir" => "ex",
+        "f#" | "fsharp" => "fs",
+        "haskell" => "hs",
+        "javascript" => "js",
+        "kotlin" => "kt",
+        "markdown" => "md",
+        "objective-c" => "m",
+        "perl" => "pl",
+        "python" => "py",
+        "restructuredtext" => "rst",
+        "ruby" => "rb",
+        "rust" => "rs",
+        "terraform" | "hcl" => "tf",
+        "typescript" => "ts",
+        "visual basic" | "vb.net" => "vb",
+        _ => language.as_str(),
+    };
+    extension.to_string()
+}
+
 const TEXT_FILES: [&str; 4] = ["", "adoc", "md", "txt"];
 pub(crate) fn is_text_file(extension: &str) -> bool {
     TEXT_FILES.contains(&extension)
@@ -140,3 +271,332 @@ pub fn extension_from_path(path: &Path) -> String {
 pub fn extension(filename: &str) -> String {
     extension_from_path(Path::new(filename))
 }
+
+/// Characters, besides whitespace, that may precede snippet markers in comment-style target files
+/// such as blockquotes (`> `) and Javadoc comments (`* `)
+const MARKER_PREFIX_CHARS: [char; 2] = ['>', '*'];
+
+/// Whether files with the given extension are Markdown, AsciiDoc or reStructuredText
+pub(crate) fn is_markup(extension: &str) -> bool {
+    matches!(extension, "adoc" | "md" | "rst")
+}
+
+/// Split a target line into the prefix preceding a potential snippet marker and the remainder.
+/// Markup prefixes consist of blockquotes and list markers, e.g. `> ` or `- `, while other
+/// prefixes consist of [`MARKER_PREFIX_CHARS`].
+pub(crate) fn split_marker_prefix(line: &str, markup: bool) -> (&str, &str) {
+    if !markup {
+        let marker = line
+            .trim_start_matches(|c: char| c.is_whitespace() || MARKER_PREFIX_CHARS.contains(&c));
+        return line.split_at(line.len() - marker.len());
+    }
+
+    let mut marker = line.trim_start();
+    loop {
+        let rest = match marker.strip_prefix('>') {
+            Some(rest) => rest,
+            None => &marker[list_marker_len(marker)..],
+        };
+
+        if rest.len() == marker.len() {
+            break;
+        }
+        marker = rest.trim_start();
+    }
+
+    line.split_at(line.len() - marker.len())
+}
+
+/// Length of the bullet (`*`, `-`, `+`) or ordered (`1.`, `1)`) list marker starting `line`, or 0
+/// when `line` doesn't start with a list marker followed by whitespace
+fn list_marker_len(line: &str) -> usize {
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let len = match line[digits..].chars().next() {
+        Some('*' | '-' | '+') if digits == 0 => 1,
+        Some('.' | ')') if digits > 0 => digits + 1,
+        _ => return 0,
+    };
+
+    if line[len..].starts_with(char::is_whitespace) {
+        len
+    } else {
+        0
+    }
+}
+
+/// Prefix that continues the list item or blockquote a markup snippet marker is in. List markers
+/// are replaced by indentation so rendered lines belong to the list item instead of starting new
+/// ones.
+pub(crate) fn continuation_prefix(prefix: &str) -> String {
+    prefix
+        .chars()
+        .map(|c| {
+            if c == '>' || c.is_whitespace() {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect()
+}
+
+/// Write `content` to `path` unless the file already has the same content so unchanged files keep
+/// their modification time. Files are written atomically by renaming a temporary file written in
+/// the same directory and existing files retain their permissions. Returns whether the file was
+/// written.
+pub(crate) fn write_if_changed(path: &Path, content: &[u8]) -> SnippextResult<bool> {
+    let permissions = match fs::metadata(path) {
+        Ok(metadata) => {
+            if metadata.len() == content.len() as u64 && fs::read(path)? == content {
+                return Ok(false);
+            }
+
+            Some(metadata.permissions())
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+
+    // replace the file a symlink points to rather than the symlink itself
+    let path = match permissions {
+        Some(_) => fs::canonicalize(path)?,
+        None => path.to_path_buf(),
+    };
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    if permissions.is_none() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(".snippext");
+    // temporary files are only readable by their owner whereas new files should get the same
+    // permissions as any other file created by the user
+    #[cfg(unix)]
+    if permissions.is_none() {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(fs::Permissions::from_mode(0o666));
+    }
+
+    let mut file = builder.tempfile_in(dir)?;
+    file.write_all(content)?;
+    if let Some(permissions) = permissions {
+        file.as_file().set_permissions(permissions)?;
+    }
+    file.persist(&path).map_err(|e| e.error)?;
+    Ok(true)
+}
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Line ending, trailing newline and byte order mark of a text file so files can be rewritten
+/// without changing anything other than their content.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct TextFormat {
+    pub line_ending: LineEnding,
+    pub trailing_newline: bool,
+    pub bom: bool,
+}
+
+impl TextFormat {
+    /// Detect the format of `content`. The line ending used by most lines is used for the whole
+    /// file, ties go to the line ending of the first line, and defaults to `\n` for single line
+    /// files.
+    pub fn detect(content: &[u8]) -> Self {
+        let mut crlf = 0;
+        let mut lf = 0;
+        let mut first = None;
+        for i in memchr::memchr_iter(b'\n', content) {
+            let line_ending = if i > 0 && content[i - 1] == b'\r' {
+                crlf += 1;
+                LineEnding::Crlf
+            } else {
+                lf += 1;
+                LineEnding::Lf
+            };
+            first.get_or_insert(line_ending);
+        }
+
+        let line_ending = match crlf.cmp(&lf) {
+            Ordering::Greater => LineEnding::Crlf,
+            Ordering::Less => LineEnding::Lf,
+            Ordering::Equal => first.unwrap_or(LineEnding::Lf),
+        };
+
+        Self {
+            line_ending,
+            trailing_newline: content.ends_with(b"\n"),
+            bom: content.starts_with(UTF8_BOM),
+        }
+    }
+
+    /// Use `line_ending`, when provided, rather than the detected line ending
+    pub fn with_line_ending(self, line_ending: Option<LineEnding>) -> Self {
+        Self {
+            line_ending: line_ending.unwrap_or(self.line_ending),
+            ..self
+        }
+    }
+
+    /// `content` without a UTF-8 byte order mark
+    pub fn strip_bom(content: &[u8]) -> &[u8] {
+        content.strip_prefix(UTF8_BOM).unwrap_or(content)
+    }
+
+    /// Join `lines` into file content using this format
+    pub fn join<S: AsRef<str>>(&self, lines: &[S]) -> String {
+        let newline = self.line_ending.as_str();
+        let mut content = String::new();
+        if self.bom {
+            content.push('\u{FEFF}');
+        }
+
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                content.push_str(newline);
+            }
+            content.push_str(line.as_ref());
+        }
+
+        if self.trailing_newline && !lines.is_empty() {
+            content.push_str(newline);
+        }
+
+        content
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::{
+        continuation_prefix, split_marker_prefix, write_if_changed, SnippextComments, TextFormat,
+    };
+    use crate::types::LineEnding;
+
+    #[test]
+    fn should_skip_content_without_markers() {
+        let comments = SnippextComments::new("rs", "snippet::start", "snippet::end");
+        assert!(!comments.may_contain_snippets(b"fn main() {}\n"));
+        assert!(comments.may_contain_snippets(b"// snippet::start main\nfn main() {}\n"));
+    }
+
+    #[test]
+    fn should_include_region_markers() {
+        let comments = SnippextComments::new("cs", "snippet::start", "snippet::end");
+        assert!(comments.may_contain_snippets(b"#region console\n"));
+
+        let comments = SnippextComments::new("vb", "snippet::start", "snippet::end");
+        assert!(comments.may_contain_snippets(b"#Region \"console\"\n"));
+    }
+
+    #[test]
+    fn should_capture_key_and_attributes() {
+        let comments = SnippextComments::new("md", "snippet::start", "snippet::end");
+        let line = "<!-- snippet::start main {\"template\": \"raw\"} -->";
+        let comment = comments.is_line_start_snippet(line).unwrap();
+        assert_eq!(
+            Some(("main", Some("{\"template\": \"raw\"}"))),
+            comment.captures(line)
+        );
+
+        assert!(comments.is_line_start_snippet("fn main() {}").is_none());
+        assert!(comments
+            .is_line_end_snippet("<!-- snippet::end -->")
+            .is_some());
+    }
+
+    #[test]
+    fn should_preserve_text_format() {
+        let content = b"\xEF\xBB\xBF# README\r\n\r\nContent\r\n";
+        let format = TextFormat::detect(content);
+        assert_eq!(
+            TextFormat {
+                line_ending: LineEnding::Crlf,
+                trailing_newline: true,
+                bom: true,
+            },
+            format
+        );
+
+        let lines = String::from_utf8_lossy(TextFormat::strip_bom(content))
+            .lines()
+            .map(String::from)
+            .collect::<Vec<String>>();
+        assert_eq!(content.as_slice(), format.join(&lines).as_bytes());
+
+        let format =
+            TextFormat::detect(b"# README\nContent").with_line_ending(Some(LineEnding::Crlf));
+        assert_eq!("# README\r\nContent", format.join(&["# README", "Content"]));
+    }
+
+    #[test]
+    fn should_detect_majority_line_ending() {
+        let mixed = b"# README\n\r\nContent\r\nMore\r\n";
+        assert_eq!(LineEnding::Crlf, TextFormat::detect(mixed).line_ending);
+
+        let mixed = b"# README\r\n\nContent\nMore\n";
+        assert_eq!(LineEnding::Lf, TextFormat::detect(mixed).line_ending);
+
+        let tie = b"# README\r\nContent\nMore";
+        assert_eq!(LineEnding::Crlf, TextFormat::detect(tie).line_ending);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_only_write_changed_content_and_keep_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("docs/README.md");
+        assert!(write_if_changed(&path, b"# README").unwrap());
+        assert!(!write_if_changed(&path, b"# README").unwrap());
+        // new files aren't restricted to their owner like temporary files
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_ne!(0, mode & 0o044);
+        assert_eq!(1, fs::read_dir(path.parent().unwrap()).unwrap().count());
+
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o754)).unwrap();
+        assert!(write_if_changed(&path, b"# CHANGED").unwrap());
+        assert_eq!("# CHANGED", fs::read_to_string(&path).unwrap());
+        assert_eq!(
+            0o754,
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777
+        );
+        assert_eq!(1, fs::read_dir(path.parent().unwrap()).unwrap().count());
+    }
+
+    #[test]
+    fn should_split_marker_prefix() {
+        assert_eq!(
+            ("   > ", "<!-- snippet::start main -->"),
+            split_marker_prefix("   > <!-- snippet::start main -->", true)
+        );
+        assert_eq!(
+            (" * ", "// snippet::start main"),
+            split_marker_prefix(" * // snippet::start main", false)
+        );
+        assert_eq!(
+            ("> - ", "<!-- snippet::start main -->"),
+            split_marker_prefix("> - <!-- snippet::start main -->", true)
+        );
+        assert_eq!(
+            ("10. ", "<!-- snippet::start main -->"),
+            split_marker_prefix("10. <!-- snippet::start main -->", true)
+        );
+        assert_eq!(("", "**Note**"), split_marker_prefix("**Note**", true));
+        assert_eq!(("", "# README"), split_marker_prefix("# README", true));
+    }
+
+    #[test]
+    fn should_replace_list_markers_with_continuation_indentation() {
+        assert_eq!("  ", continuation_prefix("* "));
+        assert_eq!(">    ", continuation_prefix("> 1. "));
+        assert_eq!("   > ", continuation_prefix("   > "));
+    }
+}
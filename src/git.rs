@@ -39,7 +39,7 @@ pub(crate) fn checkout_files(
         )?));
     }
 
-    if cone_patterns.is_some() {
+    if let Some(cone_patterns) = cone_patterns {
         let sparse_checkout_init = Command::new("git")
             .arg("sparse-checkout")
             .arg("init")
@@ -57,7 +57,7 @@ pub(crate) fn checkout_files(
         let sparse_checkout_set = Command::new("git")
             .arg("sparse-checkout")
             .arg("set")
-            .arg(cone_patterns.unwrap().join(" "))
+            .arg(cone_patterns.join(" "))
             .current_dir(dir)
             .output()
             .map_err(SnippextError::from)?;
@@ -0,0 +1,127 @@
+use std::collections::{HashMap, HashSet};
+
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::constants::SNIPPEXT_GROUP_ATTRIBUTE;
+use crate::types::Snippet;
+
+/// Snippets collected into groups, e.g. the same example written in several languages, so they can
+/// be rendered together into a target. A snippet belongs to the group named by its `group`
+/// attribute, otherwise the prefix of its identifier before the last `group_separator`, otherwise
+/// its identifier. Members keep the order they were extracted in.
+#[derive(Debug, Default)]
+pub(crate) struct SnippetGroups {
+    separator: Option<String>,
+    groups: IndexMap<String, Vec<Snippet>>,
+    /// Languages each snippet identifier was extracted in
+    languages: HashMap<String, HashSet<String>>,
+}
+
+impl SnippetGroups {
+    pub fn new(separator: Option<String>) -> Self {
+        Self {
+            separator: separator.filter(|s| !s.is_empty()),
+            groups: IndexMap::new(),
+            languages: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, snippet: &Snippet) {
+        let lang = snippet
+            .attributes
+            .get("lang")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        self.languages
+            .entry(snippet.identifier.clone())
+            .or_default()
+            .insert(lang.to_string());
+
+        let members = self.groups.entry(self.group_name(snippet)).or_default();
+        // overlapping sources can extract the same snippet more than once
+        let exists = members.iter().any(|m| {
+            m.identifier == snippet.identifier
+                && m.path == snippet.path
+                && m.start_line == snippet.start_line
+        });
+        if !exists {
+            members.push(snippet.clone());
+        }
+    }
+
+    pub fn get(&self, group: &str) -> Option<&[Snippet]> {
+        self.groups.get(group).map(Vec::as_slice)
+    }
+
+    /// Whether snippets with `identifier` were extracted in more than one language. Only one of
+    /// them can be rendered by a marker that isn't a group marker.
+    pub fn has_multiple_languages(&self, identifier: &str) -> bool {
+        self.languages
+            .get(identifier)
+            .is_some_and(|languages| languages.len() > 1)
+    }
+
+    fn group_name(&self, snippet: &Snippet) -> String {
+        if let Some(Value::String(group)) = snippet.attributes.get(SNIPPEXT_GROUP_ATTRIBUTE) {
+            return group.clone();
+        }
+
+        self.separator
+            .as_deref()
+            .and_then(|separator| snippet.identifier.rsplit_once(separator))
+            .map(|(prefix, _)| prefix)
+            .unwrap_or(&snippet.identifier)
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use serde_json::Value;
+
+    use super::SnippetGroups;
+    use crate::types::Snippet;
+
+    fn snippet(id: &str, path: &str, group: Option<&str>) -> Snippet {
+        let mut attributes = HashMap::new();
+        if let Some(group) = group {
+            attributes.insert("group".to_string(), Value::String(group.to_string()));
+        }
+
+        Snippet {
+            identifier: id.to_string(),
+            path: PathBuf::from(path),
+            text: String::new(),
+            attributes,
+            start_line: 1,
+            end_line: 2,
+            source_link: None,
+            line_numbers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn should_group_snippets_by_id_prefix_and_attribute() {
+        let mut groups = SnippetGroups::new(Some(".".to_string()));
+        groups.add(&snippet("connect", "main.rs", None));
+        groups.add(&snippet("connect", "main.py", None));
+        groups.add(&snippet("connect", "main.py", None));
+        groups.add(&snippet("connect.go", "main.go", None));
+        groups.add(&snippet("open_connection", "main.ts", Some("connect")));
+        groups.add(&snippet("disconnect", "main.rs", None));
+
+        let paths = groups
+            .get("connect")
+            .unwrap()
+            .iter()
+            .map(|s| s.path.to_string_lossy().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(vec!["main.rs", "main.py", "main.go", "main.ts"], paths);
+        assert_eq!(1, groups.get("disconnect").unwrap().len());
+        assert!(groups.get("connect.go").is_none());
+    }
+}
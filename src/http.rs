@@ -0,0 +1,365 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chrono::DateTime;
+use reqwest::blocking::Client;
+use reqwest::header::{
+    HeaderMap, HeaderName, CACHE_CONTROL, ETAG, EXPIRES, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+    LAST_MODIFIED,
+};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::error::SnippextError;
+use crate::{files, SnippextResult};
+
+/// HTTP caching details recorded for each downloaded URL.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(crate) struct CacheMetadata {
+    /// URL the cached content was downloaded from
+    pub url: String,
+    /// `ETag` response header used for `If-None-Match` revalidation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// `Last-Modified` response header used for `If-Modified-Since` revalidation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    /// Number of seconds, from `fetched_at`, the cached content is considered fresh.
+    /// When absent the content must always be revalidated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<u64>,
+    /// Seconds since the unix epoch when the content was last downloaded or revalidated
+    pub fetched_at: u64,
+}
+
+impl CacheMetadata {
+    fn from_headers(url: &str, headers: &HeaderMap) -> Self {
+        Self {
+            url: url.to_string(),
+            etag: header_string(headers, ETAG),
+            last_modified: header_string(headers, LAST_MODIFIED),
+            max_age: max_age(headers),
+            fetched_at: now(),
+        }
+    }
+
+    /// Refresh metadata from the headers of a `304 Not Modified` response. Validators are only
+    /// replaced when the server sends new ones.
+    fn revalidated(mut self, headers: &HeaderMap) -> Self {
+        if let Some(etag) = header_string(headers, ETAG) {
+            self.etag = Some(etag);
+        }
+
+        if let Some(last_modified) = header_string(headers, LAST_MODIFIED) {
+            self.last_modified = Some(last_modified);
+        }
+
+        self.max_age = max_age(headers);
+        self.fetched_at = now();
+        self
+    }
+
+    fn is_fresh(&self) -> bool {
+        self.max_age
+            .is_some_and(|max_age| now() < self.fetched_at.saturating_add(max_age))
+    }
+}
+
+/// Serializes downloads so concurrent extraction never writes the same cached file at once
+static DOWNLOAD_LOCK: Mutex<()> = Mutex::new(());
+
+/// Download `url` to `path` honoring HTTP caching semantics.
+///
+/// Cached content is reused without a request while it is fresh per `Cache-Control: max-age`
+/// (or `Expires`). Otherwise it is revalidated with a conditional GET using the stored
+/// `ETag` / `Last-Modified` validators.
+pub(crate) fn download(url: &str, path: &Path) -> SnippextResult<()> {
+    let _guard = DOWNLOAD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let metadata_path = cache_metadata_path(path);
+    let metadata = if path.exists() {
+        read_cache_metadata(&metadata_path).filter(|m| m.url == url)
+    } else {
+        None
+    };
+
+    if metadata.as_ref().is_some_and(|m| m.is_fresh()) {
+        debug!("using cached content for {}", url);
+        return Ok(());
+    }
+
+    let mut request = Client::new().get(url);
+    if let Some(metadata) = &metadata {
+        if let Some(etag) = &metadata.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+
+        if let Some(last_modified) = &metadata.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let mut response = request.send()?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if let Some(metadata) = metadata {
+            debug!("cached content for {} has not been modified", url);
+            write_cache_metadata(&metadata_path, &metadata.revalidated(response.headers()))?;
+            return Ok(());
+        }
+    }
+
+    if !response.status().is_success() {
+        return Err(SnippextError::GeneralError(format!(
+            "Failed to download {}. Status: {}",
+            url,
+            response.status()
+        )));
+    }
+
+    // without metadata a partially downloaded file is never considered fresh or revalidated
+    match fs::remove_file(&metadata_path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+
+    // the body is written to a temporary file and renamed so a failed download never leaves
+    // truncated content in place of the cached file
+    let metadata = CacheMetadata::from_headers(url, response.headers());
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let mut file = tempfile::Builder::new()
+        .prefix(".snippext")
+        .tempfile_in(dir)?;
+    response.copy_to(&mut file)?;
+    file.persist(path).map_err(|e| e.error)?;
+    write_cache_metadata(&metadata_path, &metadata)?;
+
+    Ok(())
+}
+
+fn cache_metadata_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".cache.json");
+    path.with_file_name(file_name)
+}
+
+fn read_cache_metadata(path: &Path) -> Option<CacheMetadata> {
+    let content = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(content.as_str()) {
+        Ok(metadata) => Some(metadata),
+        Err(e) => {
+            warn!("ignoring invalid cache metadata {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+fn write_cache_metadata(path: &Path, metadata: &CacheMetadata) -> SnippextResult<()> {
+    files::write_if_changed(path, serde_json::to_string(metadata)?.as_bytes())?;
+    Ok(())
+}
+
+fn header_string(headers: &HeaderMap, name: HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string())
+}
+
+/// Determine how long a response may be cached from its `Cache-Control` directives,
+/// falling back to `Expires` when no `max-age` is provided.
+fn max_age(headers: &HeaderMap) -> Option<u64> {
+    if let Some(cache_control) = header_string(headers, CACHE_CONTROL) {
+        let mut max_age = None;
+        for directive in cache_control.split(',') {
+            let directive = directive.trim().to_ascii_lowercase();
+            if directive == "no-cache" || directive == "no-store" {
+                return None;
+            }
+
+            if let Some(value) = directive.strip_prefix("max-age=") {
+                max_age = value.trim_matches('"').parse::<u64>().ok();
+            }
+        }
+
+        if max_age.is_some() {
+            return max_age;
+        }
+    }
+
+    // Wed, 16 Aug 2023 22:40:19 GMT
+    let expires = header_string(headers, EXPIRES)?;
+    let expires = DateTime::parse_from_rfc2822(expires.as_str()).ok()?;
+    Some(
+        u64::try_from(expires.timestamp())
+            .unwrap_or(0)
+            .saturating_sub(now()),
+    )
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::{fs, thread};
+
+    use tempfile::tempdir;
+
+    /// Minimal HTTP/1.1 stand-in that records each request and answers with the response
+    /// returned by `respond`
+    fn serve<F>(respond: F) -> (String, Arc<Mutex<Vec<String>>>)
+    where
+        F: Fn(&str) -> String + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/snippet.rs", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+
+                let request = String::from_utf8_lossy(&request).to_ascii_lowercase();
+                let response = respond(request.as_str());
+                recorded.lock().unwrap().push(request);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (url, requests)
+    }
+
+    fn response(status: &str, headers: &[&str], body: &str) -> String {
+        let mut response = format!("HTTP/1.1 {}\r\nConnection: close\r\n", status);
+        for header in headers {
+            response.push_str(header);
+            response.push_str("\r\n");
+        }
+        response.push_str(format!("Content-Length: {}\r\n\r\n{}", body.len(), body).as_str());
+        response
+    }
+
+    #[test]
+    fn should_use_cached_content_while_fresh() {
+        let (url, requests) = serve(|_| {
+            response(
+                "200 OK",
+                &["Cache-Control: public, max-age=600", "ETag: \"v1\""],
+                "fn main() {}",
+            )
+        });
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("snippet_rs");
+
+        super::download(url.as_str(), &path).unwrap();
+        super::download(url.as_str(), &path).unwrap();
+
+        assert_eq!(1, requests.lock().unwrap().len());
+        assert_eq!("fn main() {}", fs::read_to_string(&path).unwrap());
+    }
+
+    #[test]
+    fn should_revalidate_with_conditional_get() {
+        let (url, requests) = serve(|request| {
+            if request.contains("if-none-match: \"v1\"") {
+                response("304 Not Modified", &["ETag: \"v1\""], "")
+            } else {
+                response(
+                    "200 OK",
+                    &[
+                        "Cache-Control: no-cache",
+                        "ETag: \"v1\"",
+                        "Last-Modified: Wed, 16 Aug 2023 22:40:19 GMT",
+                    ],
+                    "fn main() {}",
+                )
+            }
+        });
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("snippet_rs");
+
+        super::download(url.as_str(), &path).unwrap();
+        super::download(url.as_str(), &path).unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(2, requests.len());
+        assert!(requests.iter().all(|r| r.starts_with("get ")));
+        assert!(requests[1].contains("if-none-match: \"v1\""));
+        assert!(requests[1].contains("if-modified-since: wed, 16 aug 2023 22:40:19 gmt"));
+        assert_eq!("fn main() {}", fs::read_to_string(&path).unwrap());
+    }
+
+    #[test]
+    fn should_replace_cached_content_when_modified() {
+        let (url, _) = serve(|request| {
+            if request.contains("if-none-match: \"v1\"") {
+                response("200 OK", &["ETag: \"v2\""], "fn updated() {}")
+            } else {
+                response("200 OK", &["ETag: \"v1\""], "fn main() {}")
+            }
+        });
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("snippet_rs");
+
+        super::download(url.as_str(), &path).unwrap();
+        super::download(url.as_str(), &path).unwrap();
+
+        assert_eq!("fn updated() {}", fs::read_to_string(&path).unwrap());
+        let metadata = super::read_cache_metadata(&super::cache_metadata_path(&path)).unwrap();
+        assert_eq!(Some("\"v2\"".to_string()), metadata.etag);
+    }
+
+    #[test]
+    fn should_keep_cached_content_and_drop_metadata_when_download_fails() {
+        let (url, requests) = serve(|request| {
+            if request.contains("if-none-match: \"v1\"") {
+                // connection closes before the announced body length is sent
+                "HTTP/1.1 200 OK\r\nConnection: close\r\nETag: \"v2\"\r\nContent-Length: 100\r\n\r\nfn upd"
+                    .to_string()
+            } else {
+                response("200 OK", &["ETag: \"v1\""], "fn main() {}")
+            }
+        });
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("snippet_rs");
+
+        super::download(url.as_str(), &path).unwrap();
+        assert!(super::download(url.as_str(), &path).is_err());
+
+        assert_eq!("fn main() {}", fs::read_to_string(&path).unwrap());
+        assert!(!super::cache_metadata_path(&path).exists());
+        // the temporary file is removed along with the failed download
+        assert_eq!(1, fs::read_dir(dir.path()).unwrap().count());
+
+        // without metadata the next download isn't conditional so the content is repaired
+        super::download(url.as_str(), &path).unwrap();
+        assert!(!requests.lock().unwrap()[2].contains("if-none-match"));
+    }
+}
@@ -0,0 +1,416 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::settings::SnippextSettings;
+use crate::types::Snippet;
+use crate::{files, SnippextResult};
+
+/// Bumped whenever the index format changes so older indexes are discarded
+const INDEX_VERSION: u32 = 1;
+
+/// Persisted record of what a previous `extract` run read and wrote
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct IndexFile {
+    version: u32,
+    /// Hash of the settings used to produce the index. Any setting or template change
+    /// invalidates the whole index.
+    settings: String,
+    sources: BTreeMap<String, SourceEntry>,
+    targets: BTreeMap<String, TargetEntry>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct SourceEntry {
+    /// Modification time, in nanoseconds since the unix epoch
+    mtime: u64,
+    len: u64,
+    hash: String,
+    snippets: Vec<Snippet>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct TargetEntry {
+    hash: String,
+    /// Hashes of the snippets, by identifier, rendered into the target
+    snippets: BTreeMap<String, String>,
+}
+
+/// Incremental extraction index. Sources whose modification time and size, or content, are
+/// unchanged reuse the snippets from the previous run and targets are only re-rendered when
+/// their content or a snippet they reference changed.
+pub(crate) struct SnippextIndex {
+    path: Option<PathBuf>,
+    settings: String,
+    previous: IndexFile,
+    sources: Mutex<BTreeMap<String, SourceEntry>>,
+    targets: Mutex<BTreeMap<String, TargetEntry>>,
+}
+
+impl SnippextIndex {
+    /// Load the index configured by `index_file`. The index is disabled when `index_file` isn't
+    /// set and empty when the file doesn't exist or was produced with different settings.
+    pub fn load(settings: &SnippextSettings) -> Self {
+        let path = settings.index_file.as_ref().map(PathBuf::from);
+        let fingerprint = settings_fingerprint(settings);
+        let previous = path
+            .as_ref()
+            .filter(|p| p.exists())
+            .and_then(|p| match read_index_file(p) {
+                Ok(index) => Some(index),
+                Err(e) => {
+                    warn!("ignoring invalid index {:?}: {}", p, e);
+                    None
+                }
+            })
+            .filter(|index| index.version == INDEX_VERSION && index.settings == fingerprint)
+            .unwrap_or_default();
+
+        Self {
+            path,
+            settings: fingerprint,
+            previous,
+            sources: Default::default(),
+            targets: Default::default(),
+        }
+    }
+
+    /// Index that neither reuses nor records anything, e.g. for sources whose files are recreated
+    /// on every run
+    pub fn disabled() -> Self {
+        Self {
+            path: None,
+            settings: String::new(),
+            previous: IndexFile::default(),
+            sources: Default::default(),
+            targets: Default::default(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Snippets from the previous run when the source file's modification time and size are
+    /// unchanged. The file doesn't need to be read.
+    pub fn unchanged_source(&self, path: &Path, metadata: &Metadata) -> Option<Vec<Snippet>> {
+        let key = path.to_string_lossy();
+        let entry = self.previous.sources.get(key.as_ref())?;
+        if entry.mtime != mtime(metadata) || entry.len != metadata.len() {
+            return None;
+        }
+
+        self.sources
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.to_string(), entry.clone());
+        Some(entry.snippets.clone())
+    }
+
+    /// Snippets from the previous run when the source file's content is unchanged
+    pub fn unchanged_content(
+        &self,
+        path: &Path,
+        metadata: &Metadata,
+        content: &[u8],
+    ) -> Option<Vec<Snippet>> {
+        let entry = self.previous.sources.get(path.to_string_lossy().as_ref())?;
+        let hash = hash(content);
+        if entry.hash != hash {
+            return None;
+        }
+
+        self.sources
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(
+                path.to_string_lossy().to_string(),
+                SourceEntry {
+                    mtime: mtime(metadata),
+                    len: metadata.len(),
+                    hash,
+                    snippets: entry.snippets.clone(),
+                },
+            );
+        Some(entry.snippets.clone())
+    }
+
+    /// Record the snippets extracted from a source file
+    pub fn record_source(
+        &self,
+        path: &Path,
+        metadata: &Metadata,
+        content: &[u8],
+        snippets: &[Snippet],
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        self.sources
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(
+                path.to_string_lossy().to_string(),
+                SourceEntry {
+                    mtime: mtime(metadata),
+                    len: metadata.len(),
+                    hash: hash(content),
+                    snippets: snippets.to_vec(),
+                },
+            );
+    }
+
+    /// Determine whether a target is unchanged since the previous run and all the snippets it
+    /// references are the same
+    pub fn unchanged_target(
+        &self,
+        path: &Path,
+        content: &[u8],
+        snippets: &HashMap<String, Snippet>,
+    ) -> bool {
+        let key = path.to_string_lossy();
+        let Some(entry) = self.previous.targets.get(key.as_ref()) else {
+            return false;
+        };
+
+        if entry.hash != hash(content) {
+            return false;
+        }
+
+        let unchanged = entry.snippets.iter().all(|(id, snippet_hash)| {
+            snippets
+                .get(id)
+                .is_some_and(|s| &hash_snippet(s) == snippet_hash)
+        });
+
+        if unchanged {
+            self.targets
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(key.to_string(), entry.clone());
+        }
+
+        unchanged
+    }
+
+    /// Record a processed target along with the snippets rendered into it
+    pub fn record_target(&self, path: &Path, content: &[u8], snippets: Vec<&Snippet>) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        self.targets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(
+                path.to_string_lossy().to_string(),
+                TargetEntry {
+                    hash: hash(content),
+                    snippets: snippets
+                        .into_iter()
+                        .map(|s| (s.identifier.clone(), hash_snippet(s)))
+                        .collect(),
+                },
+            );
+    }
+
+    /// Write the sources and targets recorded during this run
+    pub fn save(self) -> SnippextResult<()> {
+        let Some(path) = self.path else {
+            return Ok(());
+        };
+
+        let index = IndexFile {
+            version: INDEX_VERSION,
+            settings: self.settings,
+            sources: self.sources.into_inner().unwrap_or_else(|e| e.into_inner()),
+            targets: self.targets.into_inner().unwrap_or_else(|e| e.into_inner()),
+        };
+
+        debug!(
+            "writing index {:?} with {} sources and {} targets",
+            &path,
+            index.sources.len(),
+            index.targets.len()
+        );
+        files::write_if_changed(&path, serde_json::to_string(&index)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+fn read_index_file(path: &Path) -> SnippextResult<IndexFile> {
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(content.as_str())?)
+}
+
+/// Hash of the settings that affect extracted and rendered snippets
+fn settings_fingerprint(settings: &SnippextSettings) -> String {
+    let settings = SnippextSettings {
+        jobs: None,
+        ..settings.clone()
+    };
+
+    hash_value(&settings)
+}
+
+fn hash(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn hash_snippet(snippet: &Snippet) -> String {
+    hash_value(snippet)
+}
+
+/// Hash of a value's JSON representation. Converting to a [`serde_json::Value`] first sorts
+/// object keys so hashes are stable across runs.
+fn hash_value<T: Serialize>(value: &T) -> String {
+    let json = serde_json::to_value(value)
+        .map(|v| v.to_string())
+        .unwrap_or_default();
+    hash(json.as_bytes())
+}
+
+fn mtime(metadata: &Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map(|d| u64::try_from(d.as_nanos()).unwrap_or(u64::MAX))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::SnippextIndex;
+    use crate::settings::SnippextSettings;
+    use crate::types::Snippet;
+
+    fn snippet(text: &str) -> Snippet {
+        Snippet {
+            identifier: "main".to_string(),
+            path: "main.rs".into(),
+            text: text.to_string(),
+            attributes: Default::default(),
+            start_line: 1,
+            end_line: 3,
+            source_link: None,
+            line_numbers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn should_reuse_unchanged_sources_and_targets() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("main.rs");
+        let target = dir.path().join("README.md");
+        fs::write(&source, "fn main() {}").unwrap();
+        fs::write(&target, "# README").unwrap();
+
+        let settings = SnippextSettings {
+            index_file: Some(dir.path().join("index.json").to_string_lossy().to_string()),
+            ..Default::default()
+        };
+
+        let metadata = fs::metadata(&source).unwrap();
+        let index = SnippextIndex::load(&settings);
+        assert!(index.unchanged_source(&source, &metadata).is_none());
+        index.record_source(
+            &source,
+            &metadata,
+            b"fn main() {}",
+            &[snippet("fn main() {}")],
+        );
+        index.record_target(&target, b"# README", vec![&snippet("fn main() {}")]);
+        index.save().unwrap();
+
+        let index = SnippextIndex::load(&settings);
+        assert_eq!(1, index.unchanged_source(&source, &metadata).unwrap().len());
+
+        let snippets = HashMap::from([("main".to_string(), snippet("fn main() {}"))]);
+        assert!(index.unchanged_target(&target, b"# README", &snippets));
+        assert!(!index.unchanged_target(&target, b"# CHANGED", &snippets));
+
+        let snippets = HashMap::from([("main".to_string(), snippet("fn main() { run() }"))]);
+        assert!(!index.unchanged_target(&target, b"# README", &snippets));
+    }
+
+    #[test]
+    fn should_discard_index_when_settings_change() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("main.rs");
+        fs::write(&source, "fn main() {}").unwrap();
+
+        let settings = SnippextSettings {
+            index_file: Some(dir.path().join("index.json").to_string_lossy().to_string()),
+            ..Default::default()
+        };
+
+        let metadata = fs::metadata(&source).unwrap();
+        let index = SnippextIndex::load(&settings);
+        index.record_source(&source, &metadata, b"fn main() {}", &[]);
+        index.save().unwrap();
+
+        let index = SnippextIndex::load(&SnippextSettings {
+            start: "tag::".to_string(),
+            ..settings
+        });
+        assert!(index.unchanged_source(&source, &metadata).is_none());
+    }
+
+    #[test]
+    fn should_not_rewrite_unchanged_index() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("main.rs");
+        let index_file = dir.path().join("index.json");
+        fs::write(&source, "fn main() {}").unwrap();
+
+        let settings = SnippextSettings {
+            index_file: Some(index_file.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+
+        let metadata = fs::metadata(&source).unwrap();
+        let index = SnippextIndex::load(&settings);
+        index.record_source(&source, &metadata, b"fn main() {}", &[]);
+        index.save().unwrap();
+        let modified = fs::metadata(&index_file).unwrap().modified().unwrap();
+
+        let index = SnippextIndex::load(&settings);
+        index.record_source(&source, &metadata, b"fn main() {}", &[]);
+        index.save().unwrap();
+        assert_eq!(
+            modified,
+            fs::metadata(&index_file).unwrap().modified().unwrap()
+        );
+    }
+
+    #[test]
+    fn should_not_record_with_disabled_index() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("main.rs");
+        fs::write(&source, "fn main() {}").unwrap();
+
+        let metadata = fs::metadata(&source).unwrap();
+        let index = SnippextIndex::disabled();
+        assert!(!index.is_enabled());
+        index.record_source(&source, &metadata, b"fn main() {}", &[]);
+        assert!(index.unchanged_source(&source, &metadata).is_none());
+        index.save().unwrap();
+    }
+}
@@ -2,17 +2,26 @@
 #![doc(issue_tracker_base_url = "https://github.com/doctavious/snippext/issues/")]
 #![deny(rustdoc::broken_intra_doc_links)]
 
+mod archive;
+mod catalog;
 pub mod cli;
 pub mod cmd;
+mod code_blocks;
 pub mod constants;
+mod encoding;
 pub mod error;
 mod files;
 pub mod git;
+mod groups;
+mod http;
+mod index;
+mod manifest;
 pub mod sanitize;
 pub mod settings;
 pub mod templates;
 pub mod types;
 pub mod unindent;
+mod walk;
 
 use crate::error::SnippextError;
 use crate::settings::SnippextSettings;
@@ -0,0 +1,170 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::constants::OUTPUT_MANIFEST_FILE;
+use crate::error::SnippextError;
+use crate::{files, SnippextResult};
+
+/// Bumped whenever the manifest format changes
+const MANIFEST_VERSION: u32 = 1;
+
+/// Record of the files Snippext generated in an output directory so stale files can be pruned and
+/// generated output cleared without touching files Snippext didn't create.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(crate) struct OutputManifest {
+    version: u32,
+    /// Generated files relative to the output directory
+    files: BTreeSet<String>,
+}
+
+impl OutputManifest {
+    pub fn new<I: IntoIterator<Item = String>>(files: I) -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            files: files.into_iter().collect(),
+        }
+    }
+
+    /// Load the manifest of `output_dir`. The manifest is empty when the file doesn't exist or
+    /// can't be read.
+    pub fn load(output_dir: &Path) -> Self {
+        let path = output_dir.join(OUTPUT_MANIFEST_FILE);
+        if !path.exists() {
+            return Self::default();
+        }
+
+        match fs::read_to_string(&path)
+            .map_err(SnippextError::from)
+            .and_then(|content| Ok(serde_json::from_str::<Self>(&content)?))
+        {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                warn!("ignoring invalid output manifest {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn files(&self) -> &BTreeSet<String> {
+        &self.files
+    }
+
+    /// Add files recorded in another manifest
+    pub fn extend(&mut self, other: OutputManifest) {
+        self.files.extend(other.files);
+    }
+
+    pub fn save(&self, output_dir: &Path) -> SnippextResult<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        files::write_if_changed(&output_dir.join(OUTPUT_MANIFEST_FILE), content.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Remove `files`, relative to `output_dir`, along with any directories left empty. Paths that
+/// would escape the output directory are never removed. Returns the removed files.
+pub(crate) fn remove_files<'a, I: IntoIterator<Item = &'a String>>(
+    output_dir: &Path,
+    files: I,
+) -> SnippextResult<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    for file in files {
+        let relative_path = Path::new(file);
+        if !relative_path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            warn!(
+                "not removing {} as it is outside of the output directory",
+                file
+            );
+            continue;
+        }
+
+        let path = output_dir.join(relative_path);
+        if !path.is_file() {
+            continue;
+        }
+
+        fs::remove_file(&path)?;
+        removed.push(path.clone());
+
+        let mut dir = path.parent();
+        while let Some(parent) = dir.filter(|d| *d != output_dir) {
+            // only succeeds for empty directories
+            if fs::remove_dir(parent).is_err() {
+                break;
+            }
+            dir = parent.parent();
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Remove all generated files recorded in the manifest of `output_dir` along with the manifest
+pub(crate) fn clear_output_dir(output_dir: &Path) -> SnippextResult<Vec<PathBuf>> {
+    let manifest = OutputManifest::load(output_dir);
+    let removed = remove_files(output_dir, manifest.files())?;
+
+    let manifest_path = output_dir.join(OUTPUT_MANIFEST_FILE);
+    if manifest_path.exists() {
+        fs::remove_file(manifest_path)?;
+    }
+
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::{clear_output_dir, remove_files, OutputManifest};
+
+    #[test]
+    fn should_only_remove_files_within_output_dir() {
+        let dir = tempdir().unwrap();
+        let output_dir = dir.path().join("output");
+        fs::create_dir_all(output_dir.join("src")).unwrap();
+        fs::write(output_dir.join("src/main_default.md"), "").unwrap();
+        fs::write(dir.path().join("README.md"), "").unwrap();
+
+        let removed = remove_files(
+            &output_dir,
+            &[
+                "src/main_default.md".to_string(),
+                "../README.md".to_string(),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(vec![output_dir.join("src/main_default.md")], removed);
+        assert!(!output_dir.join("src").exists());
+        assert!(dir.path().join("README.md").exists());
+    }
+
+    #[test]
+    fn should_clear_manifest_files() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("main_default.md"), "").unwrap();
+        fs::write(dir.path().join("notes.md"), "").unwrap();
+        OutputManifest::new(["main_default.md".to_string()])
+            .save(dir.path())
+            .unwrap();
+
+        assert_eq!(1, clear_output_dir(dir.path()).unwrap().len());
+        assert_eq!(
+            vec!["notes.md"],
+            fs::read_dir(dir.path())
+                .unwrap()
+                .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+                .collect::<Vec<String>>()
+        );
+    }
+}
@@ -66,7 +66,7 @@ fn sanitize_with_options<S: AsRef<str>>(name: S, options: Options) -> String {
 #[cfg(test)]
 mod tests {
     // From https://github.com/parshap/node-sanitize-filename/blob/master/test.js
-    static NAMES: &'static [&'static str] = &[
+    static NAMES: &[&str] = &[
         "the quick brown fox jumped over the lazy dog",
         "résumé",
         "hello\u{0000}world",
@@ -111,7 +111,7 @@ mod tests {
         "LPT9.asdf",
     ];
 
-    static NAMES_CLEANED: &'static [&'static str] = &[
+    static NAMES_CLEANED: &[&str] = &[
         "the quick brown fox jumped over the lazy dog",
         "résumé",
         "helloworld",
@@ -167,8 +167,8 @@ mod tests {
             );
         }
 
-        let long = std::iter::repeat('a').take(300).collect::<String>();
-        let shorter = std::iter::repeat('a').take(255).collect::<String>();
+        let long = "a".repeat(300);
+        let shorter = "a".repeat(255);
         assert_eq!(super::sanitize(long), shorter);
     }
 }
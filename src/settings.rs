@@ -5,16 +5,24 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
 use crate::constants::{
-    DEFAULT_END, DEFAULT_OUTPUT_DIR, DEFAULT_OUTPUT_FILE_EXTENSION, DEFAULT_SOURCE_FILES,
-    DEFAULT_START, DEFAULT_TEMPLATE, DEFAULT_TEMPLATE_IDENTIFIER,
+    DEFAULT_END, DEFAULT_MAX_FILE_SIZE, DEFAULT_OUTPUT_DIR, DEFAULT_OUTPUT_FILE_EXTENSION,
+    DEFAULT_SOURCE_FILES, DEFAULT_START, DEFAULT_TAB_WIDTH, DEFAULT_TEMPLATE,
+    DEFAULT_TEMPLATE_IDENTIFIER,
+};
+use crate::types::{
+    Dedent, LineEnding, LinkFormat, MissingSnippetsBehavior, SnippetSource, SourceOverrides,
+    TemplateEngine,
 };
-use crate::types::{LinkFormat, MissingSnippetsBehavior, SnippetSource};
 use crate::SnippextResult;
 
 const fn _default_true() -> bool {
     true
 }
 
+const fn _default_tab_width() -> usize {
+    DEFAULT_TAB_WIDTH
+}
+
 /// Snippext configuration settings
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SnippextSettings {
@@ -24,6 +32,13 @@ pub struct SnippextSettings {
     pub end: String,
     /// Templates used to render Snippets
     pub templates: IndexMap<String, String>,
+    /// Engine used to render templates
+    #[serde(default)]
+    pub template_engine: TemplateEngine,
+    /// Engines, by template identifier, for templates that don't use `template_engine`. Templates
+    /// loaded from `.hbs` and `.j2` files are assigned an engine based on their extension.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub template_engines: IndexMap<String, TemplateEngine>,
     /// Defines where source snippets should be extracted from
     pub sources: Vec<SnippetSource>,
     /// Directory in which the generated snippet files be will output to
@@ -32,9 +47,31 @@ pub struct SnippextSettings {
     /// Extension for generated files written to the output directory.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub output_extension: Option<String>,
+    /// Extensions, by template identifier, for generated files. Templates without an entry use
+    /// `output_extension`.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub output_extensions: IndexMap<String, String>,
+    /// Handlebars template for the path, relative to the output directory, of generated files.
+    /// Available variables are `id`, `template`, `path`, `dir`, `filename`, `lang` and `ext`.
+    /// Defaults to `{{path}}/{{id}}_{{template}}.{{ext}}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_path_template: Option<String>,
     /// List of glob patters that contain the files to be spliced with the code snippets.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub targets: Option<Vec<String>>,
+    /// Template identifiers, by target file extension, e.g. `adoc`, or glob, e.g. `docs/**/*.html`,
+    /// used to render snippets in matching targets that don't have a `template` attribute. The
+    /// first matching entry is used.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub target_templates: IndexMap<String, String>,
+    /// Separator between a snippet group and member name, e.g. `.` groups `connect.rust` and
+    /// `connect.python` into `connect`. Without a separator only snippets sharing an identifier or
+    /// `group` attribute are grouped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_separator: Option<String>,
+    /// Template used to render snippet groups into targets. Defaults to `preset:markdown-group`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_template: Option<String>,
     /// Defines the format of snippet source links that appear under each snippet.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub link_format: Option<LinkFormat>,
@@ -57,6 +94,67 @@ pub struct SnippextSettings {
     /// to render snippets.
     #[serde(default)]
     pub selected_lines_include_ellipses: bool,
+    /// Whether rendered snippets are numbered with the line numbers of the source file. Can be
+    /// overridden per snippet with the `line_numbers` attribute.
+    #[serde(default)]
+    pub line_numbers: bool,
+    /// How much common leading whitespace is removed from rendered snippets: `true` to remove all
+    /// of it, `false` to keep it or a maximum number of columns. Can be overridden per snippet with
+    /// the `dedent` attribute.
+    #[serde(default)]
+    pub dedent: Dedent,
+    /// Number of columns a tab advances to when measuring indentation
+    #[serde(default = "_default_tab_width")]
+    pub tab_width: usize,
+    /// Determines whether tabs within the indentation of rendered snippets are replaced by spaces.
+    /// Can be overridden per snippet with the `expand_tabs` attribute.
+    #[serde(default)]
+    pub expand_tabs: bool,
+    /// Number of threads used to extract snippets and process target files. Defaults to the
+    /// number of available CPUs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<usize>,
+    /// File used to record extracted sources and processed targets so unchanged files can be
+    /// skipped on subsequent runs. Extraction isn't incremental when not provided. The file's
+    /// directory is never walked for source files so it should be dedicated to Snippext.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_file: Option<String>,
+    /// Determines whether `.gitignore`, `.ignore` and `.snippextignore` files are honored when
+    /// walking local and git sources.
+    #[serde(default = "_default_true")]
+    pub respect_ignore_files: bool,
+    /// Source files, command output and stdin larger than this size, in bytes, are skipped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_file_size: Option<u64>,
+    /// Encoding, e.g. `windows-1252` or `latin1`, used to decode source files that are neither
+    /// valid UTF-8 nor start with a byte order mark. Such files are skipped when not provided.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_encoding: Option<String>,
+    /// Line ending used when writing target files. Defaults to each target's existing line
+    /// ending.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_endings: Option<LineEnding>,
+    /// Determines whether snippet markers within fenced, delimited or literal code blocks of
+    /// Markdown, AsciiDoc and reStructuredText targets are processed. Individual markers can opt
+    /// in with the `code_block` attribute.
+    #[serde(default)]
+    pub code_block_markers: bool,
+    /// Determines whether files previously generated in the output directory that are no longer
+    /// produced are removed. Generated files are tracked in a manifest within the output directory.
+    #[serde(default)]
+    pub prune_output_dir: bool,
+    /// Determines whether a `snippets.json` index listing every extracted snippet, its source
+    /// location, attributes and generated files is written to the output directory.
+    #[serde(default)]
+    pub generate_snippets_index: bool,
+    /// Determines whether an `index.md` listing every extracted snippet is also written to the
+    /// output directory. Requires `generate_snippets_index`.
+    #[serde(default)]
+    pub generate_markdown_index: bool,
+    /// Determines whether rendering a template that references an undefined variable is an error.
+    /// By default undefined variables are rendered as empty strings.
+    #[serde(default)]
+    pub strict_templates: bool,
 }
 
 impl Default for SnippextSettings {
@@ -69,6 +167,11 @@ impl Default for SnippextSettings {
     /// output_dir: [`DEFAULT_OUTPUT_DIR`]
     /// missing_snippets_behavior: [`MissingSnippetsBehavior::default()`]
     /// enable_autodetect_language: true
+    /// max_file_size: [`DEFAULT_MAX_FILE_SIZE`]
+    /// index_file: none. Unlike the default configuration file, which enables
+    /// [`DEFAULT_INDEX_FILE`](crate::constants::DEFAULT_INDEX_FILE), the index is opt-in when
+    /// settings are built in code given a shared relative index file would make separate runs in
+    /// the same working directory, e.g. tests, skip each other's files.
     fn default() -> Self {
         Self {
             start: String::from(DEFAULT_START),
@@ -77,12 +180,19 @@ impl Default for SnippextSettings {
                 String::from(DEFAULT_TEMPLATE_IDENTIFIER),
                 DEFAULT_TEMPLATE.to_string(),
             )]),
-            sources: vec![SnippetSource::Local {
-                files: vec![String::from(DEFAULT_SOURCE_FILES)],
-            }],
+            template_engine: TemplateEngine::default(),
+            template_engines: IndexMap::new(),
+            sources: vec![SnippetSource::new_local(vec![String::from(
+                DEFAULT_SOURCE_FILES,
+            )])],
             output_dir: Some(String::from(DEFAULT_OUTPUT_DIR)),
             output_extension: Some(String::from(DEFAULT_OUTPUT_FILE_EXTENSION)),
+            output_extensions: IndexMap::new(),
+            output_path_template: None,
             targets: None,
+            target_templates: IndexMap::new(),
+            group_separator: None,
+            group_template: None,
             link_format: None,
             source_link_prefix: None,
             omit_source_links: false,
@@ -90,6 +200,21 @@ impl Default for SnippextSettings {
             retain_nested_snippet_comments: false,
             enable_autodetect_language: true,
             selected_lines_include_ellipses: false,
+            line_numbers: false,
+            dedent: Dedent::default(),
+            tab_width: DEFAULT_TAB_WIDTH,
+            expand_tabs: false,
+            jobs: None,
+            index_file: None,
+            respect_ignore_files: true,
+            max_file_size: Some(DEFAULT_MAX_FILE_SIZE),
+            fallback_encoding: None,
+            line_endings: None,
+            code_block_markers: false,
+            prune_output_dir: false,
+            generate_snippets_index: false,
+            generate_markdown_index: false,
+            strict_templates: false,
         }
     }
 }
@@ -105,4 +230,30 @@ impl SnippextSettings {
         let settings = serde_json::from_str(content.as_str())?;
         Ok(settings)
     }
+
+    /// Settings with the overrides of an individual snippet source applied
+    pub(crate) fn with_source_overrides(&self, overrides: &SourceOverrides) -> Self {
+        let mut settings = self.clone();
+        if let Some(start) = &overrides.start {
+            settings.start = start.clone();
+        }
+
+        if let Some(end) = &overrides.end {
+            settings.end = end.clone();
+        }
+
+        if overrides.link_format.is_some() {
+            settings.link_format = overrides.link_format;
+        }
+
+        if overrides.source_link_prefix.is_some() {
+            settings.source_link_prefix = overrides.source_link_prefix.clone();
+        }
+
+        if let Some(retain_nested_snippet_comments) = overrides.retain_nested_snippet_comments {
+            settings.retain_nested_snippet_comments = retain_nested_snippet_comments;
+        }
+
+        settings
+    }
 }
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use handlebars::{no_escape, Handlebars, RenderError, Template};
+use minijinja::{AutoEscape, Environment, UndefinedBehavior};
+use serde_json::Value;
+
+use super::helpers;
+use crate::SnippextResult;
+
+/// Renders templates registered with a template engine
+pub(crate) trait Renderer: Send + Sync {
+    /// Render the registered template `identifier` with `data`
+    fn render(&self, identifier: &str, data: &HashMap<String, Value>) -> SnippextResult<String>;
+}
+
+/// Handlebars templates. Templates are available to each other as partials, e.g.
+/// `{{> source_link}}`, and partial blocks allow templates to extend a layout, e.g.
+/// `{{#> _layout}}{{snippet}}{{/_layout}}` where `_layout` includes `{{> @partial-block}}`.
+pub(crate) struct HandlebarsRenderer {
+    hbs: Handlebars<'static>,
+}
+
+impl HandlebarsRenderer {
+    pub fn new<'a, I>(templates: I, strict: bool) -> SnippextResult<Self>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut hbs = handlebars();
+        hbs.set_strict_mode(strict);
+        for (identifier, template) in templates {
+            hbs.register_template_string(identifier, template)
+                .map_err(RenderError::from)?;
+        }
+
+        Ok(Self { hbs })
+    }
+
+    /// Description, including the line and column, of a syntax error within the template
+    pub fn compile_error(template: &str) -> Option<String> {
+        let error = Template::compile(template).err()?;
+        Some(match (error.line_no, error.column_no) {
+            (Some(line), Some(column)) => {
+                format!("at line {}, column {}. {}", line, column, error.reason())
+            }
+            _ => error.reason().to_string(),
+        })
+    }
+}
+
+impl Renderer for HandlebarsRenderer {
+    fn render(&self, identifier: &str, data: &HashMap<String, Value>) -> SnippextResult<String> {
+        Ok(self.hbs.render(identifier, data)?)
+    }
+}
+
+/// Jinja2 templates rendered by MiniJinja. Templates can include, import and extend each other by
+/// identifier, e.g. `{% extends "_layout" %}`.
+pub(crate) struct MiniJinjaRenderer {
+    env: Environment<'static>,
+}
+
+impl MiniJinjaRenderer {
+    pub fn new<'a, I>(templates: I, strict: bool) -> SnippextResult<Self>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let mut env = minijinja();
+        env.set_undefined_behavior(if strict {
+            UndefinedBehavior::Strict
+        } else {
+            UndefinedBehavior::Lenient
+        });
+        for (identifier, template) in templates {
+            env.add_template_owned(identifier.to_string(), template.to_string())?;
+        }
+
+        Ok(Self { env })
+    }
+
+    /// Description, including the line, of a syntax error within the template
+    pub fn compile_error(template: &str) -> Option<String> {
+        let env = minijinja();
+        let error = env.template_from_str(template).err()?;
+        let reason = error
+            .detail()
+            .map(String::from)
+            .unwrap_or_else(|| error.to_string());
+        Some(match error.line() {
+            Some(line) => format!("at line {}. {}", line, reason),
+            None => reason.to_string(),
+        })
+    }
+}
+
+impl Renderer for MiniJinjaRenderer {
+    fn render(&self, identifier: &str, data: &HashMap<String, Value>) -> SnippextResult<String> {
+        Ok(self.env.get_template(identifier)?.render(data)?)
+    }
+}
+
+pub(crate) fn handlebars() -> Handlebars<'static> {
+    let mut hbs = Handlebars::new();
+    hbs.register_escape_fn(no_escape);
+    helpers::register_helpers(&mut hbs);
+    hbs
+}
+
+fn minijinja() -> Environment<'static> {
+    let mut env = Environment::new();
+    // rendered snippets are code rather than HTML
+    env.set_auto_escape_callback(|_| AutoEscape::None);
+    // keep the trailing newline of templates consistent with Handlebars
+    env.set_keep_trailing_newline(true);
+    helpers::register_filters(&mut env);
+    env
+}
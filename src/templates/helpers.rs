@@ -0,0 +1,390 @@
+use std::fmt::Write;
+
+use handlebars::{
+    handlebars_helper, Context, Handlebars, Helper, HelperDef, RenderContext, RenderError,
+    ScopedJson,
+};
+use minijinja::value::Kwargs;
+use minijinja::{Environment, ErrorKind};
+use serde_json::Value;
+
+use crate::unindent::unindent;
+
+handlebars_helper!(indent: |text: str, width: Json| {
+    let prefix = match width {
+        Value::Number(n) => " ".repeat(n.as_u64().unwrap_or_default() as usize),
+        Value::String(s) => s.clone(),
+        _ => String::new(),
+    };
+    indent_lines(text, &prefix)
+});
+
+handlebars_helper!(dedent: |text: str| unindent(text));
+
+handlebars_helper!(escape_html: |text: str| handlebars::html_escape(text));
+
+handlebars_helper!(escape_markdown: |text: str| escape_markdown_text(text));
+
+handlebars_helper!(line_numbers: |text: Json, {start: u64 = 1, separator: str = " "}| {
+    numbered_listing(text, start, separator)
+});
+
+handlebars_helper!(lines: |text: str, start: u64, end: u64| select_lines(text, start, end));
+
+handlebars_helper!(replace: |text: str, from: str, to: str| text.replace(from, to));
+
+handlebars_helper!(trim: |text: str| text.trim());
+
+handlebars_helper!(upper: |text: str| text.to_uppercase());
+
+handlebars_helper!(lower: |text: str| text.to_lowercase());
+
+handlebars_helper!(json: |value: Json, {pretty: bool = false}| {
+    if pretty {
+        serde_json::to_string_pretty(value).unwrap_or_default()
+    } else {
+        value.to_string()
+    }
+});
+
+/// Helper that falls back to a value when the first is missing, null or empty. Implemented directly
+/// rather than via `handlebars_helper!` so missing values don't error in strict mode.
+struct DefaultHelper;
+
+impl HelperDef for DefaultHelper {
+    fn call_inner<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+    ) -> Result<ScopedJson<'reg, 'rc>, RenderError> {
+        let value = h.param(0).map(|p| p.value()).unwrap_or(&Value::Null);
+        let missing = match value {
+            Value::Null => true,
+            Value::String(s) => s.is_empty(),
+            _ => false,
+        };
+
+        let result = if missing {
+            h.param(1).map(|p| p.value().clone()).unwrap_or(Value::Null)
+        } else {
+            value.clone()
+        };
+        Ok(ScopedJson::Derived(result))
+    }
+}
+
+handlebars_helper!(lang_alias: |lang: Json| {
+    lang.as_str().map(code_fence_language).unwrap_or_default()
+});
+
+handlebars_helper!(lang_label: |lang: Json| {
+    lang.as_str().map(language_label).unwrap_or_default()
+});
+
+/// Characters with special meaning in Markdown that are escaped by `escape_markdown`
+const MARKDOWN_SPECIAL_CHARS: [char; 17] = [
+    '\\', '`', '*', '_', '{', '}', '[', ']', '(', ')', '#', '+', '-', '.', '!', '|', '<',
+];
+
+/// Register the built-in helpers available to all templates
+pub(crate) fn register_helpers(hbs: &mut Handlebars) {
+    hbs.register_helper("indent", Box::new(indent));
+    hbs.register_helper("dedent", Box::new(dedent));
+    hbs.register_helper("escape_html", Box::new(escape_html));
+    hbs.register_helper("escape_markdown", Box::new(escape_markdown));
+    hbs.register_helper("line_numbers", Box::new(line_numbers));
+    hbs.register_helper("lines", Box::new(lines));
+    hbs.register_helper("replace", Box::new(replace));
+    hbs.register_helper("trim", Box::new(trim));
+    hbs.register_helper("upper", Box::new(upper));
+    hbs.register_helper("lower", Box::new(lower));
+    hbs.register_helper("json", Box::new(json));
+    hbs.register_helper("default", Box::new(DefaultHelper));
+    hbs.register_helper("lang_alias", Box::new(lang_alias));
+    hbs.register_helper("lang_label", Box::new(lang_label));
+}
+
+/// Register filters, in addition to MiniJinja's built-in filters, available to Jinja templates.
+/// MiniJinja already provides `indent`, `replace`, `trim`, `upper`, `lower`, `tojson` and
+/// `default`.
+pub(crate) fn register_filters(env: &mut Environment) {
+    env.add_filter("dedent", |text: String| unindent(&text));
+    env.add_filter("escape_html", |text: String| handlebars::html_escape(&text));
+    env.add_filter("escape_markdown", |text: String| {
+        escape_markdown_text(&text)
+    });
+    env.add_filter(
+        "line_numbers",
+        |text: minijinja::Value, kwargs: Kwargs| -> Result<String, minijinja::Error> {
+            let start = kwargs.get::<Option<u64>>("start")?.unwrap_or(1);
+            let separator = kwargs.get::<Option<String>>("separator")?;
+            kwargs.assert_all_used()?;
+            let text = serde_json::to_value(&text).map_err(|e| {
+                minijinja::Error::new(
+                    ErrorKind::InvalidOperation,
+                    format!("line_numbers filter could not read its value. {}", e),
+                )
+            })?;
+            Ok(numbered_listing(
+                &text,
+                start,
+                separator.as_deref().unwrap_or(" "),
+            ))
+        },
+    );
+    env.add_filter("lines", |text: String, start: u64, end: u64| {
+        select_lines(&text, start, end)
+    });
+    env.add_filter("lang_alias", |lang: Option<String>| {
+        lang.as_deref().map(code_fence_language).unwrap_or_default()
+    });
+    env.add_filter("lang_label", |lang: Option<String>| {
+        lang.as_deref().map(language_label).unwrap_or_default()
+    });
+}
+
+/// Prefix non-empty lines
+fn indent_lines(text: &str, prefix: &str) -> String {
+    map_lines(text, |line| {
+        if line.trim().is_empty() {
+            line.to_string()
+        } else {
+            format!("{}{}", prefix, line)
+        }
+    })
+}
+
+fn escape_markdown_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if MARKDOWN_SPECIAL_CHARS.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Prefix lines with right-aligned line numbers starting at `start`
+fn number_lines(text: &str, start: u64, separator: &str) -> String {
+    let count = text.lines().count() as u64;
+    let width = (start + count.saturating_sub(1)).to_string().len();
+    let mut number = start;
+    map_lines(text, |line| {
+        let numbered = format!("{:>width$}{}{}", number, separator, line, width = width);
+        number += 1;
+        numbered
+    })
+}
+
+/// Number either snippet text, starting at `start`, or the structured `lines` of a snippet using
+/// their source line numbers
+fn numbered_listing(text: &Value, start: u64, separator: &str) -> String {
+    match text {
+        Value::Array(source_lines) => number_source_lines(source_lines, separator),
+        _ => number_lines(text.as_str().unwrap_or_default(), start, separator),
+    }
+}
+
+/// Prefix `source_lines`, each with a `number` and `text`, with their right-aligned source line numbers.
+/// Lines without a number, such as ellipses, are padded instead.
+fn number_source_lines(source_lines: &[Value], separator: &str) -> String {
+    let width = source_lines
+        .iter()
+        .filter_map(|line| line["number"].as_u64())
+        .max()
+        .unwrap_or_default()
+        .to_string()
+        .len();
+    let mut numbered = String::new();
+    for line in source_lines {
+        let number = line["number"]
+            .as_u64()
+            .map(|n| n.to_string())
+            .unwrap_or_default();
+        let text = line["text"].as_str().unwrap_or_default();
+        let _ = writeln!(
+            numbered,
+            "{:>width$}{}{}",
+            number,
+            separator,
+            text,
+            width = width
+        );
+    }
+    numbered
+}
+
+/// Lines `start` through `end`, inclusive and starting at 1
+fn select_lines(text: &str, start: u64, end: u64) -> String {
+    let start = start.max(1) as usize;
+    let selected = text
+        .lines()
+        .skip(start - 1)
+        .take((end as usize + 1).saturating_sub(start))
+        .collect::<Vec<&str>>();
+    if selected.is_empty() {
+        String::new()
+    } else {
+        selected.join("\n") + "\n"
+    }
+}
+
+/// Apply `f` to every line of `text` preserving a trailing newline
+fn map_lines<F: FnMut(&str) -> String>(text: &str, f: F) -> String {
+    let mut mapped = text.lines().map(f).collect::<Vec<String>>().join("\n");
+    if text.ends_with('\n') {
+        mapped.push('\n');
+    }
+    mapped
+}
+
+/// Map language names, as detected by hyperpolyglot, to names commonly used for code fences by
+/// syntax highlighters. Unknown names are lowercased.
+pub(crate) fn code_fence_language(lang: &str) -> String {
+    let lang = lang.to_ascii_lowercase();
+    let alias = match lang.as_str() {
+        "c#" => "csharp",
+        "c++" => "cpp",
+        "f#" => "fsharp",
+        "emacs lisp" => "elisp",
+        "objective-c" => "objectivec",
+        "objective-c++" => "objectivecpp",
+        "protocol buffer" => "protobuf",
+        "shell" => "bash",
+        "visual basic .net" | "vb.net" => "vbnet",
+        _ => return lang.replace(' ', "-"),
+    };
+    alias.to_string()
+}
+
+/// Display name of a language, e.g. for tab labels. Languages detected by hyperpolyglot are
+/// lowercased so well known names are restored and others are capitalized.
+pub(crate) fn language_label(lang: &str) -> String {
+    let label = match lang.to_ascii_lowercase().as_str() {
+        "c#" | "csharp" => "C#",
+        "c++" | "cpp" => "C++",
+        "f#" | "fsharp" => "F#",
+        "javascript" | "js" => "JavaScript",
+        "typescript" | "ts" => "TypeScript",
+        "php" => "PHP",
+        "sql" => "SQL",
+        "html" => "HTML",
+        "css" => "CSS",
+        "json" => "JSON",
+        "yaml" => "YAML",
+        "objective-c" => "Objective-C",
+        "powershell" => "PowerShell",
+        _ => {
+            return lang
+                .split(' ')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|c| c.to_uppercase().chain(chars).collect())
+                        .unwrap_or_default()
+                })
+                .collect::<Vec<String>>()
+                .join(" ")
+        }
+    };
+    label.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use handlebars::{no_escape, Handlebars};
+    use serde_json::json;
+
+    use super::register_helpers;
+
+    fn render(template: &str, data: serde_json::Value) -> String {
+        let mut hbs = Handlebars::new();
+        hbs.register_escape_fn(no_escape);
+        register_helpers(&mut hbs);
+        hbs.render_template(template, &data).unwrap()
+    }
+
+    #[test]
+    fn should_transform_snippet_lines() {
+        let data = json!({"snippet": "fn main() {\n\n    run();\n}\n"});
+        assert_eq!(
+            "  fn main() {\n\n      run();\n  }\n",
+            render("{{indent snippet 2}}", data.clone())
+        );
+        assert_eq!(
+            "> fn main() {\n\n>     run();\n> }\n",
+            render("{{indent snippet \"> \"}}", data.clone())
+        );
+        assert_eq!(
+            "run();\n",
+            render("{{dedent (lines snippet 3 3)}}", data.clone())
+        );
+        assert_eq!(
+            " 9 fn main() {\n10 \n11     run();\n12 }\n",
+            render("{{line_numbers snippet start=9}}", data.clone())
+        );
+        assert_eq!(
+            "  9 fn main() {\n    // ...\n101     run();\n",
+            render(
+                "{{line_numbers lines}}",
+                json!({"lines": [
+                    {"number": 9, "text": "fn main() {", "highlighted": false},
+                    {"number": null, "text": "// ...", "highlighted": false},
+                    {"number": 101, "text": "    run();", "highlighted": true},
+                ]})
+            )
+        );
+        assert_eq!(
+            "1: fn main() {\n",
+            render(
+                "{{line_numbers (lines snippet 1 1) separator=\": \"}}",
+                data
+            )
+        );
+    }
+
+    #[test]
+    fn should_default_missing_values_in_strict_mode() {
+        let mut hbs = Handlebars::new();
+        hbs.set_strict_mode(true);
+        register_helpers(&mut hbs);
+        assert_eq!(
+            "text",
+            hbs.render_template("{{default lang \"text\"}}", &json!({}))
+                .unwrap()
+        );
+        assert!(hbs.render_template("{{lang}}", &json!({})).is_err());
+    }
+
+    #[test]
+    fn should_transform_strings() {
+        let data = json!({"text": " <a href='#'>*x*</a> ", "lang": "C#", "attrs": {"a": 1}});
+        assert_eq!(
+            " &lt;a href&#x3D;&#x27;#&#x27;&gt;*x*&lt;/a&gt; ",
+            render("{{escape_html text}}", data.clone())
+        );
+        assert_eq!(
+            "\\*x\\*",
+            render("{{escape_markdown \"*x*\"}}", data.clone())
+        );
+        assert_eq!(
+            "<A HREF='#'>*X*</A>",
+            render("{{upper (trim text)}}", data.clone())
+        );
+        assert_eq!("c#", render("{{lower lang}}", data.clone()));
+        assert_eq!("csharp", render("{{lang_alias lang}}", data.clone()));
+        assert_eq!("C#", render("{{lang_label \"c#\"}}", data.clone()));
+        assert_eq!("Go", render("{{lang_label \"go\"}}", data.clone()));
+        assert_eq!(
+            "a-b",
+            render("{{replace \"a_b\" \"_\" \"-\"}}", data.clone())
+        );
+        assert_eq!("{\"a\":1}", render("{{json attrs}}", data.clone()));
+        assert_eq!("text", render("{{default missing \"text\"}}", data.clone()));
+        assert_eq!("C#", render("{{default lang \"text\"}}", data));
+    }
+}
@@ -0,0 +1,584 @@
+mod engines;
+mod helpers;
+mod presets;
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use glob::Pattern;
+use indexmap::IndexSet;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::constants::{
+    DEFAULT_GROUP_TEMPLATE, DEFAULT_TEMPLATE_IDENTIFIER, PARTIAL_TEMPLATE_PREFIX,
+    SNIPPEXT_TEMPLATE_ATTRIBUTE,
+};
+use crate::error::SnippextError;
+use crate::settings::SnippextSettings;
+use crate::templates::engines::{HandlebarsRenderer, MiniJinjaRenderer, Renderer};
+use crate::types::{Dedent, Snippet, TemplateEngine};
+use crate::unindent::{unindent_with, UnindentOptions};
+use crate::{files, SnippextResult};
+
+pub(crate) fn render_template(
+    registry: &TemplateRegistry,
+    identifier: Option<&String>,
+    snippet: &Snippet,
+    snippext_settings: &SnippextSettings,
+    target: Option<&Path>,
+    target_attributes: Option<HashMap<String, Value>>,
+) -> SnippextResult<String> {
+    let data = snippet_data(snippet, snippext_settings, target, target_attributes)?;
+    let target_template = target.and_then(|t| registry.target_template(t));
+    let template = get_template(identifier, &data, target_template, snippext_settings)?;
+    registry.render(&template, &data)
+}
+
+/// Render all members of a snippet group through a group template. The template receives the
+/// `group` name and the `snippets` of the group, each with the same data as a single snippet along
+/// with a `label`, e.g. for tabs, taken from the `tab` attribute, the language or the identifier.
+pub(crate) fn render_group_template(
+    registry: &TemplateRegistry,
+    group: &str,
+    members: &[Snippet],
+    snippext_settings: &SnippextSettings,
+    target: Option<&Path>,
+    target_attributes: Option<HashMap<String, Value>>,
+) -> SnippextResult<String> {
+    let mut snippets = Vec::with_capacity(members.len());
+    for member in members {
+        let mut member_data =
+            snippet_data(member, snippext_settings, target, target_attributes.clone())?;
+        let attribute = |name: &str| {
+            member
+                .attributes
+                .get(name)
+                .and_then(Value::as_str)
+                .filter(|v| !v.is_empty())
+        };
+        let label = match (attribute("tab"), attribute("lang")) {
+            (Some(tab), _) => tab.to_string(),
+            (None, Some(lang)) => helpers::language_label(lang),
+            (None, None) => member.identifier.clone(),
+        };
+        member_data.insert("label".to_string(), Value::String(label));
+        snippets.push(Value::Object(member_data.into_iter().collect()));
+    }
+
+    let mut data: HashMap<String, Value> = HashMap::new();
+    data.insert(
+        "omit_source_link".to_string(),
+        Value::Bool(snippext_settings.omit_source_links),
+    );
+    if let Some(target_attributes) = target_attributes {
+        data.extend(target_attributes);
+    }
+    data.insert("group".to_string(), Value::String(group.to_string()));
+    data.insert("snippets".to_string(), Value::Array(snippets));
+    if let Some(target) = target {
+        data.insert(
+            "target_path".to_string(),
+            Value::String(target.to_string_lossy().to_string()),
+        );
+    }
+
+    let group_template = snippext_settings
+        .group_template
+        .as_deref()
+        .unwrap_or(DEFAULT_GROUP_TEMPLATE);
+    let template = get_template(None, &data, Some(group_template), snippext_settings)?;
+    registry.render(&template, &data)
+}
+
+/// Data available to templates rendering a snippet
+fn snippet_data(
+    snippet: &Snippet,
+    snippext_settings: &SnippextSettings,
+    target: Option<&Path>,
+    target_attributes: Option<HashMap<String, Value>>,
+) -> SnippextResult<HashMap<String, Value>> {
+    let mut data: HashMap<String, Value> = HashMap::new();
+    data.insert(
+        "omit_source_link".to_string(),
+        Value::Bool(snippext_settings.omit_source_links),
+    );
+
+    data.extend(snippet.attributes.clone());
+    if let Some(target_attributes) = target_attributes {
+        data.extend(target_attributes);
+    }
+
+    let source_numbers = snippet.source_line_numbers();
+    // source line number of each line of the content. Ellipses don't have a number
+    let mut content_numbers = Vec::new();
+    let snippet_content = if let Some(selected_lines) = data.get("selected_lines") {
+        let selected_numbers = selected_lines
+            .as_array()
+            .ok_or(SnippextError::GeneralError(
+                "selected_lines must be an array".to_string(),
+            ))?;
+
+        let snippet_content_lines: Vec<&str> = snippet.text.as_str().lines().collect();
+        let mut new_lines = Vec::new();
+        let mut sns = IndexSet::new();
+        for selected_number in selected_numbers {
+            let sn = selected_number.as_str().ok_or(SnippextError::GeneralError(
+                "select_lines values must be strings".to_string(),
+            ))?;
+
+            let include_lines = if sn.contains('-') {
+                let line_nums: Vec<&str> = sn.split('-').collect();
+                [
+                    line_nums[0].parse::<usize>()? - 1,
+                    line_nums[1].parse::<usize>()?,
+                ]
+            } else {
+                let num = sn.trim().parse::<usize>()?;
+                [num - 1, num]
+            };
+
+            sns.insert(include_lines);
+        }
+
+        let selected_lines_include_ellipses = data
+            .get("selected_lines_include_ellipses")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(snippext_settings.selected_lines_include_ellipses);
+
+        if selected_lines_include_ellipses {
+            let extension = &files::extension_from_path(&snippet.path);
+            let ellipsis = if files::is_text_file(extension.as_str()) {
+                String::from("...")
+            } else {
+                files::file_comments(extension)[0].0.to_owned() + " ..."
+            };
+
+            // if line 1 (index 0) isn't selected add ellipsis to start
+            if sns[0][0] != 0 {
+                new_lines.push(ellipsis.clone());
+                content_numbers.push(None);
+            }
+
+            for (idx, sn) in sns.iter().enumerate() {
+                // if there is a gap between selected lines or ranges then add an ellipsis
+                let content_lines = &snippet_content_lines[sn[0]..sn[1]];
+                // we subtract 1 from the end of the previous range because its an exclusive range.
+                if idx > 0 && sn[0] - (sns[idx - 1][1] - 1) > 1 {
+                    let line = content_lines[0];
+                    let indent = &line[..line.len() - line.trim_start().len()];
+                    let ellipsis_comment = format!("{}{}", indent, ellipsis);
+                    new_lines.push(ellipsis_comment);
+                    content_numbers.push(None);
+                }
+                let a: Vec<String> = content_lines.iter().map(|l| l.to_string()).collect();
+                new_lines.extend(a);
+                content_numbers.extend(range_numbers(&source_numbers, sn));
+            }
+
+            // if we didnt highlight the last line add an ellipsis at the end.
+            if sns
+                .last()
+                .is_some_and(|sn| sn[1] != snippet_content_lines.len())
+            {
+                new_lines.push(ellipsis);
+                content_numbers.push(None);
+            }
+        } else {
+            for sn in sns {
+                let a: Vec<String> = snippet_content_lines[sn[0]..sn[1]]
+                    .iter()
+                    .map(|l| l.to_string())
+                    .collect();
+                new_lines.extend(a);
+                content_numbers.extend(range_numbers(&source_numbers, &sn));
+            }
+        }
+
+        new_lines.iter().fold(String::new(), |mut a, b| {
+            a.push_str(b);
+            a.push('\n');
+            a
+        })
+    } else {
+        content_numbers.extend(source_numbers.into_iter().map(Some));
+        snippet.text.clone()
+    };
+
+    let dedent = match data.get("dedent") {
+        Some(dedent) => serde_json::from_value::<Dedent>(dedent.clone()).map_err(|_| {
+            SnippextError::GeneralError(
+                "dedent must be true, false or a number of columns".to_string(),
+            )
+        })?,
+        None => snippext_settings.dedent,
+    };
+    let options = UnindentOptions {
+        max_columns: dedent.max_columns(),
+        tab_width: snippext_settings.tab_width,
+        expand_tabs: data
+            .get("expand_tabs")
+            .and_then(Value::as_bool)
+            .unwrap_or(snippext_settings.expand_tabs),
+    };
+    let content = unindent_with(snippet_content.as_str(), options);
+    let lines = numbered_lines(&content, content_numbers, data.get("highlight"));
+    let line_start = lines
+        .iter()
+        .find_map(|l| l.number)
+        .unwrap_or(snippet.start_line + 1);
+    // toolchains number listings from a single start line which only matches the source when the
+    // rendered lines don't skip any, e.g. because of `selected_lines` or nested snippet comments
+    let contiguous_lines = lines
+        .iter()
+        .enumerate()
+        .all(|(i, l)| l.number == Some(line_start + i));
+    let line_numbers = data
+        .get("line_numbers")
+        .and_then(Value::as_bool)
+        .unwrap_or(snippext_settings.line_numbers);
+    data.insert("snippet".to_string(), Value::String(content));
+    data.insert("lines".to_string(), serde_json::to_value(lines)?);
+    data.insert("line_start".to_string(), Value::from(line_start));
+    data.insert(
+        "contiguous_lines".to_string(),
+        Value::Bool(contiguous_lines),
+    );
+    data.insert("line_numbers".to_string(), Value::Bool(line_numbers));
+    data.insert(
+        "source_path".to_string(),
+        Value::String(snippet.path.to_string_lossy().to_string()),
+    );
+
+    data.insert(
+        "source_link_prefix".to_string(),
+        Value::String(
+            snippext_settings
+                .source_link_prefix
+                .to_owned()
+                .unwrap_or_default(),
+        ),
+    );
+
+    if let Some(source_link) = &snippet.source_link {
+        data.insert(
+            "source_link".to_string(),
+            Value::String(source_link.to_string()),
+        );
+    }
+
+    if let Some(target) = target {
+        data.insert(
+            "target_path".to_string(),
+            Value::String(target.to_string_lossy().to_string()),
+        );
+    }
+
+    Ok(data)
+}
+
+pub(crate) fn render(content: &str, data: &HashMap<String, Value>) -> SnippextResult<String> {
+    let rendered = engines::handlebars().render_template(content, data)?;
+
+    Ok(rendered)
+}
+
+/// Configured templates compiled once per run by the engine each template uses
+pub(crate) struct TemplateRegistry {
+    engines: HashMap<String, TemplateEngine>,
+    handlebars: HandlebarsRenderer,
+    minijinja: MiniJinjaRenderer,
+    target_templates: Vec<(TargetMatcher, String)>,
+}
+
+/// Matches target files by extension or glob
+enum TargetMatcher {
+    Extension(String),
+    Glob(Pattern),
+}
+
+impl TargetMatcher {
+    fn new(value: &str) -> SnippextResult<Self> {
+        if !value.contains(['*', '?', '[', '/']) {
+            return Ok(TargetMatcher::Extension(
+                value.trim_start_matches('.').to_string(),
+            ));
+        }
+
+        Pattern::new(value.trim_start_matches("./"))
+            .map(TargetMatcher::Glob)
+            .map_err(|e| {
+                SnippextError::GlobPatternError(format!(
+                    "Glob pattern error for `{}`. {}",
+                    value, e.msg
+                ))
+            })
+    }
+
+    fn matches(&self, target: &Path) -> bool {
+        match self {
+            TargetMatcher::Extension(extension) => {
+                files::extension_from_path(target).eq_ignore_ascii_case(extension)
+            }
+            TargetMatcher::Glob(pattern) => {
+                let path = target.to_string_lossy();
+                pattern.matches(path.trim_start_matches("./"))
+            }
+        }
+    }
+}
+
+impl TemplateRegistry {
+    pub fn new(snippext_settings: &SnippextSettings) -> SnippextResult<Self> {
+        // configured templates, with preset references resolved, followed by all presets so they
+        // can be referenced by `preset:<name>` identifiers
+        let mut templates = snippext_settings
+            .templates
+            .iter()
+            .map(|(identifier, template)| {
+                let template = presets::preset(template).unwrap_or(template);
+                (
+                    identifier.clone(),
+                    template,
+                    template_engine(identifier, snippext_settings),
+                )
+            })
+            .collect::<Vec<(String, &str, TemplateEngine)>>();
+        for (identifier, template) in presets::presets() {
+            if !snippext_settings.templates.contains_key(&identifier) {
+                templates.push((identifier, template, TemplateEngine::Handlebars));
+            }
+        }
+
+        let templates_for = |engine: TemplateEngine| {
+            templates
+                .iter()
+                .filter(move |(_, _, e)| *e == engine)
+                .map(|(identifier, template, _)| (identifier.as_str(), *template))
+        };
+        let handlebars = HandlebarsRenderer::new(
+            templates_for(TemplateEngine::Handlebars),
+            snippext_settings.strict_templates,
+        )?;
+        let minijinja = MiniJinjaRenderer::new(
+            templates_for(TemplateEngine::MiniJinja),
+            snippext_settings.strict_templates,
+        )?;
+
+        let target_templates = snippext_settings
+            .target_templates
+            .iter()
+            .map(|(target, template)| Ok((TargetMatcher::new(target)?, template.clone())))
+            .collect::<SnippextResult<Vec<(TargetMatcher, String)>>>()?;
+
+        Ok(Self {
+            target_templates,
+            engines: templates
+                .into_iter()
+                .map(|(identifier, _, engine)| (identifier, engine))
+                .collect(),
+            handlebars,
+            minijinja,
+        })
+    }
+
+    /// Template configured in `target_templates` for snippets rendered into the target
+    fn target_template(&self, target: &Path) -> Option<&str> {
+        self.target_templates
+            .iter()
+            .find(|(matcher, _)| matcher.matches(target))
+            .map(|(_, template)| template.as_str())
+    }
+
+    fn render(&self, identifier: &str, data: &HashMap<String, Value>) -> SnippextResult<String> {
+        let renderer: &dyn Renderer = match self.engines.get(identifier) {
+            Some(TemplateEngine::MiniJinja) => &self.minijinja,
+            _ => &self.handlebars,
+        };
+        renderer.render(identifier, data)
+    }
+}
+
+/// Engine used to render the template. Presets are always rendered by Handlebars.
+fn template_engine(identifier: &str, snippext_settings: &SnippextSettings) -> TemplateEngine {
+    if snippext_settings
+        .templates
+        .get(identifier)
+        .is_some_and(|template| presets::is_preset(template))
+    {
+        return TemplateEngine::Handlebars;
+    }
+
+    snippext_settings
+        .template_engines
+        .get(identifier)
+        .copied()
+        .unwrap_or(snippext_settings.template_engine)
+}
+
+/// Compile all templates returning a description, including the line and, when available, column
+/// of each syntax error
+pub(crate) fn template_errors(snippext_settings: &SnippextSettings) -> Vec<String> {
+    snippext_settings
+        .templates
+        .iter()
+        .filter(|(_, template)| !template.is_empty())
+        .filter_map(|(identifier, template)| {
+            if presets::is_preset(template) {
+                return presets::preset(template).is_none().then(|| {
+                    format!(
+                        "templates[{}] {} is not a preset. Available presets are {}",
+                        identifier,
+                        template,
+                        presets::names().join(", ")
+                    )
+                });
+            }
+
+            let error = match template_engine(identifier, snippext_settings) {
+                TemplateEngine::Handlebars => HandlebarsRenderer::compile_error(template),
+                TemplateEngine::MiniJinja => MiniJinjaRenderer::compile_error(template),
+            }?;
+            Some(format!(
+                "templates[{}] is not a valid template {}",
+                identifier, error
+            ))
+        })
+        .collect()
+}
+
+/// Reason `template` doesn't refer to a configured template or a preset
+pub(crate) fn unknown_template(
+    template: &str,
+    snippext_settings: &SnippextSettings,
+) -> Option<String> {
+    if presets::is_preset(template) {
+        presets::preset(template).is_none().then(|| {
+            format!(
+                "{} is not a preset. Available presets are {}",
+                template,
+                presets::names().join(", ")
+            )
+        })
+    } else {
+        (!snippext_settings.templates.contains_key(template))
+            .then(|| format!("{} does not match a template", template))
+    }
+}
+
+/// Whether the template is only available as a partial and isn't rendered to the output directory
+pub(crate) fn is_partial_only(identifier: &str) -> bool {
+    identifier.starts_with(PARTIAL_TEMPLATE_PREFIX)
+}
+
+/// A line of rendered snippet content along with its line number within the source file
+#[derive(Debug, Serialize)]
+struct NumberedLine {
+    number: Option<usize>,
+    text: String,
+    highlighted: bool,
+}
+
+/// Source line numbers of the lines in the `[start, end)` range of snippet content
+fn range_numbers<'a>(
+    source_numbers: &'a [usize],
+    range: &[usize; 2],
+) -> impl Iterator<Item = Option<usize>> + 'a {
+    source_numbers
+        .get(range[0]..range[1].min(source_numbers.len()))
+        .unwrap_or_default()
+        .iter()
+        .copied()
+        .map(Some)
+}
+
+/// Pair each line of unindented content with its source line number. Unindenting can drop a leading
+/// blank line so numbers are aligned from the end of the content. Lines listed in the `highlight`
+/// attribute, e.g. `1 3-4` or `1,3-4`, relative to the rendered content are marked highlighted.
+fn numbered_lines(
+    content: &str,
+    numbers: Vec<Option<usize>>,
+    highlight: Option<&Value>,
+) -> Vec<NumberedLine> {
+    let highlighted = highlight
+        .and_then(Value::as_str)
+        .map(highlighted_lines)
+        .unwrap_or_default();
+    let lines = content.lines().collect::<Vec<&str>>();
+    let skip = numbers.len().saturating_sub(lines.len());
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, text)| NumberedLine {
+            number: numbers.get(skip + i).copied().flatten(),
+            text: text.to_string(),
+            highlighted: highlighted.contains(&(i + 1)),
+        })
+        .collect()
+}
+
+/// Line numbers, starting at 1, listed in a `highlight` attribute
+fn highlighted_lines(highlight: &str) -> HashSet<usize> {
+    let mut lines = HashSet::new();
+    for range in highlight
+        .trim_matches(|c| c == '{' || c == '}')
+        .split([' ', ','])
+        .filter(|r| !r.is_empty())
+    {
+        let (start, end) = range.split_once('-').unwrap_or((range, range));
+        if let (Ok(start), Ok(end)) = (start.trim().parse::<usize>(), end.trim().parse::<usize>()) {
+            lines.extend(start..=end);
+        }
+    }
+    lines
+}
+
+/// Identifier of the template used to render a snippet. In order of precedence this is the
+/// explicitly requested template, the `template` attribute, the template configured for the target
+/// and finally the default template.
+// TODO: clean up
+fn get_template(
+    identifier: Option<&String>,
+    data: &HashMap<String, Value>,
+    target_template: Option<&str>,
+    snippext_settings: &SnippextSettings,
+) -> SnippextResult<String> {
+    let lookup = |identifier: &str| {
+        if snippext_settings.templates.contains_key(identifier)
+            || presets::preset(identifier).is_some()
+        {
+            Ok(identifier.to_string())
+        } else {
+            Err(SnippextError::TemplateNotFound(format!(
+                "{} does not exist",
+                identifier
+            )))
+        }
+    };
+
+    if let Some(identifier) = identifier {
+        return lookup(identifier);
+    }
+    if let Some(template_identifier) = data.get(SNIPPEXT_TEMPLATE_ATTRIBUTE) {
+        match template_identifier {
+            Value::String(identifier) => lookup(identifier),
+            _ => Err(SnippextError::TemplateNotFound(format!(
+                "{} has wrong type",
+                template_identifier
+            ))),
+        }
+    } else if let Some(target_template) = target_template {
+        lookup(target_template)
+    } else if snippext_settings
+        .templates
+        .contains_key(DEFAULT_TEMPLATE_IDENTIFIER)
+    {
+        Ok(DEFAULT_TEMPLATE_IDENTIFIER.to_string())
+    } else {
+        // we validate that we should always have one default template
+        // so should never get here. Should we assert instead?
+        Err(SnippextError::TemplateNotFound(String::from(
+            "No default template found",
+        )))
+    }
+}
@@ -0,0 +1,235 @@
+use crate::constants::TEMPLATE_PRESET_PREFIX;
+
+/// Built-in Handlebars templates for common documentation toolchains by name. Presets ending in
+/// `-tabs` or `-group` render snippet groups.
+const PRESETS: [(&str, &str); 12] = [
+    ("asciidoc", include_str!("presets/asciidoc.hbs")),
+    ("asciidoc-tabs", include_str!("presets/asciidoc-tabs.hbs")),
+    ("docusaurus", include_str!("presets/docusaurus.hbs")),
+    (
+        "docusaurus-tabs",
+        include_str!("presets/docusaurus-tabs.hbs"),
+    ),
+    ("hugo", include_str!("presets/hugo.hbs")),
+    ("html", include_str!("presets/html.hbs")),
+    ("markdown", include_str!("presets/markdown.hbs")),
+    ("markdown-group", include_str!("presets/markdown-group.hbs")),
+    ("mdx", include_str!("presets/mdx.hbs")),
+    (
+        "mkdocs-material",
+        include_str!("presets/mkdocs-material.hbs"),
+    ),
+    (
+        "mkdocs-material-tabs",
+        include_str!("presets/mkdocs-material-tabs.hbs"),
+    ),
+    ("rst", include_str!("presets/rst.hbs")),
+];
+
+/// Whether the value refers to a preset, i.e. `preset:<name>`, regardless of whether it exists
+pub(crate) fn is_preset(value: &str) -> bool {
+    value.starts_with(TEMPLATE_PRESET_PREFIX)
+}
+
+/// Template of the preset referred to by `preset:<name>`
+pub(crate) fn preset(value: &str) -> Option<&'static str> {
+    let name = value.strip_prefix(TEMPLATE_PRESET_PREFIX)?;
+    PRESETS
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, template)| *template)
+}
+
+/// All presets along with the `preset:<name>` identifier they're registered with
+pub(crate) fn presets() -> impl Iterator<Item = (String, &'static str)> {
+    PRESETS
+        .iter()
+        .map(|(name, template)| (format!("{}{}", TEMPLATE_PRESET_PREFIX, name), *template))
+}
+
+pub(crate) fn names() -> Vec<&'static str> {
+    PRESETS.iter().map(|(name, _)| *name).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::{json, Value};
+
+    use super::PRESETS;
+    use crate::templates::engines::{HandlebarsRenderer, Renderer};
+
+    fn render(preset: &str, attributes: Value) -> String {
+        let renderer = HandlebarsRenderer::new(PRESETS, false).unwrap();
+        let mut data: HashMap<String, Value> = serde_json::from_value(json!({
+            "snippet": "fn main() {\n    println!(\"<hi>\");\n}\n",
+            "lang": "rust",
+            "source_path": "src/main.rs",
+            "source_link": "https://github.com/doctavious/snippext/blob/main/src/main.rs#L1-L3",
+            "omit_source_link": false,
+        }))
+        .unwrap();
+        data.extend(serde_json::from_value::<HashMap<String, Value>>(attributes).unwrap());
+        renderer.render(preset, &data).unwrap()
+    }
+
+    #[test]
+    fn should_compile_all_presets() {
+        for (name, template) in PRESETS {
+            assert_eq!(
+                None,
+                HandlebarsRenderer::compile_error(template),
+                "preset {}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn should_render_presets() {
+        assert_eq!(
+            "[source,rust]\n----\nfn main() {\n    println!(\"<hi>\");\n}\n----\nlink:https://github.com/doctavious/snippext/blob/main/src/main.rs#L1-L3[snippet source]\n",
+            render("asciidoc", json!({}))
+        );
+        assert_eq!(
+            ".. code-block:: rust\n   :caption: Main\n\n   fn main() {\n       println!(\"<hi>\");\n   }\n\n`snippet source <https://github.com/doctavious/snippext/blob/main/src/main.rs#L1-L3>`__\n",
+            render("rst", json!({"title": "Main"}))
+        );
+        assert_eq!(
+            "<pre><code class=\"language-rust\">fn main() {\n    println!(&quot;&lt;hi&gt;&quot;);\n}\n</code></pre>\n",
+            render("html", json!({"omit_source_link": true}))
+        );
+        assert_eq!(
+            "{{< highlight rust \"hl_lines=2\" >}}\nfn main() {\n    println!(\"<hi>\");\n}\n{{< /highlight >}}\n",
+            render("hugo", json!({"highlight": "2", "omit_source_link": true}))
+        );
+        assert_eq!(
+            "```rust title=\"src/main.rs\" {2}\nfn main() {\n    println!(\"<hi>\");\n}\n```\n",
+            render(
+                "docusaurus",
+                json!({"highlight": "2", "omit_source_link": true})
+            )
+        );
+        assert_eq!(
+            "=== \"Rust\"\n\n    ``` rust title=\"src/main.rs\"\n    fn main() {\n        println!(\"<hi>\");\n    }\n    ```\n\n    1.  Prints\n",
+            render(
+                "mkdocs-material",
+                json!({"tab": "Rust", "annotations": ["Prints"], "omit_source_link": true})
+            )
+        );
+        assert_eq!(
+            "=== \"Rust\"\n\n    ``` rust title=\"src/main.rs\"\n    fn main() {\n        println!(\"<hi>\");\n    }\n    ```\n\n    [snippet source](https://github.com/doctavious/snippext/blob/main/src/main.rs#L1-L3)\n",
+            render("mkdocs-material", json!({"tab": "Rust"}))
+        );
+    }
+
+    #[test]
+    fn should_render_line_numbers() {
+        let attributes = json!({
+            "line_numbers": true,
+            "line_start": 10,
+            "contiguous_lines": true,
+            "omit_source_link": true,
+            "lines": [
+                {"number": 10, "text": "fn main() {", "highlighted": false},
+                {"number": 11, "text": "    println!(\"<hi>\");", "highlighted": false},
+                {"number": 12, "text": "}", "highlighted": false},
+            ],
+        });
+        assert_eq!(
+            "```rust\n10 fn main() {\n11     println!(\"<hi>\");\n12 }\n```\n",
+            render("markdown", attributes.clone())
+        );
+        assert_eq!(
+            "[source%linenums,rust,start=10]\n----\nfn main() {\n    println!(\"<hi>\");\n}\n----\n",
+            render("asciidoc", attributes.clone())
+        );
+        assert_eq!(
+            ".. code-block:: rust\n   :linenos:\n   :lineno-start: 10\n\n   fn main() {\n       println!(\"<hi>\");\n   }\n\n",
+            render("rst", attributes.clone())
+        );
+        assert_eq!(
+            "```rust title=\"src/main.rs\" showLineNumbers=10\nfn main() {\n    println!(\"<hi>\");\n}\n```\n",
+            render("docusaurus", attributes.clone())
+        );
+        assert_eq!(
+            "{{< highlight rust \"linenos=table,linenostart=10\" >}}\nfn main() {\n    println!(\"<hi>\");\n}\n{{< /highlight >}}\n",
+            render("hugo", attributes.clone())
+        );
+        assert_eq!(
+            "``` rust title=\"src/main.rs\" linenums=\"10\"\nfn main() {\n    println!(\"<hi>\");\n}\n```\n",
+            render("mkdocs-material", attributes.clone())
+        );
+        assert_eq!(
+            "```rust showLineNumbers{10}\nfn main() {\n    println!(\"<hi>\");\n}\n```\n",
+            render("mdx", attributes.clone())
+        );
+        assert_eq!(
+            "<pre class=\"line-numbers\" data-start=\"10\"><code class=\"language-rust\">fn main() {\n    println!(&quot;&lt;hi&gt;&quot;);\n}\n</code></pre>\n",
+            render("html", attributes)
+        );
+    }
+
+    #[test]
+    fn should_not_number_lines_with_gaps_from_line_start() {
+        let attributes = json!({
+            "line_numbers": true,
+            "line_start": 10,
+            "contiguous_lines": false,
+            "omit_source_link": true,
+            "lines": [
+                {"number": 10, "text": "fn main() {", "highlighted": false},
+                {"number": 12, "text": "    println!(\"<hi>\");", "highlighted": false},
+                {"number": 14, "text": "}", "highlighted": false},
+            ],
+        });
+        assert_eq!(
+            "```rust\n10 fn main() {\n12     println!(\"<hi>\");\n14 }\n```\n",
+            render("markdown", attributes.clone())
+        );
+        assert_eq!(
+            "[source,rust]\n----\nfn main() {\n    println!(\"<hi>\");\n}\n----\n",
+            render("asciidoc", attributes.clone())
+        );
+        assert_eq!(
+            "{{< highlight rust >}}\nfn main() {\n    println!(\"<hi>\");\n}\n{{< /highlight >}}\n",
+            render("hugo", attributes.clone())
+        );
+        assert_eq!(
+            ".. code-block:: rust\n\n   fn main() {\n       println!(\"<hi>\");\n   }\n\n",
+            render("rst", attributes)
+        );
+    }
+
+    #[test]
+    fn should_render_group_presets() {
+        let renderer = HandlebarsRenderer::new(PRESETS, false).unwrap();
+        let data: HashMap<String, Value> = serde_json::from_value(json!({
+            "group": "connect",
+            "omit_source_link": true,
+            "snippets": [
+                {"snippet": "connect();\n", "lang": "rust", "label": "Rust", "source_path": "main.rs", "omit_source_link": true},
+                {"snippet": "connect()\n", "lang": "python", "label": "Python", "source_path": "main.py", "omit_source_link": true},
+            ],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            "**Rust**\n\n```rust\nconnect();\n```\n\n**Python**\n\n```python\nconnect()\n```\n",
+            renderer.render("markdown-group", &data).unwrap()
+        );
+        assert_eq!(
+            "<Tabs groupId=\"language\">\n<TabItem value=\"rust\" label=\"Rust\">\n\n```rust title=\"main.rs\"\nconnect();\n```\n\n</TabItem>\n<TabItem value=\"python\" label=\"Python\">\n\n```python title=\"main.py\"\nconnect()\n```\n\n</TabItem>\n</Tabs>\n",
+            renderer.render("docusaurus-tabs", &data).unwrap()
+        );
+        assert_eq!(
+            "=== \"Rust\"\n\n    ``` rust title=\"main.rs\"\n    connect();\n    ```\n\n=== \"Python\"\n\n    ``` python title=\"main.py\"\n    connect()\n    ```\n",
+            renderer.render("mkdocs-material-tabs", &data).unwrap()
+        );
+        assert_eq!(
+            "[tabs]\n====\nRust::\n+\n[source,rust]\n----\nconnect();\n----\nPython::\n+\n[source,python]\n----\nconnect()\n----\n====\n",
+            renderer.render("asciidoc-tabs", &data).unwrap()
+        );
+    }
+}
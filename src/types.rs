@@ -1,7 +1,7 @@
-use std::cell::{RefCell, RefMut};
 use std::collections::HashMap;
 use std::fmt;
 use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
 
 use clap::{Parser, ValueEnum};
 use serde::{Deserialize, Serialize};
@@ -26,6 +26,24 @@ pub struct Snippet {
     pub end_line: usize,
     /// Link to the source file the snippet is defined in
     pub source_link: Option<String>,
+    /// Line number, within the source file, of each line of the snippet's content. Lines omitted
+    /// from the content, such as nested snippet comments, are skipped.
+    #[serde(default)]
+    pub line_numbers: Vec<usize>,
+}
+
+impl Snippet {
+    /// Source line number of each line of the snippet's content
+    pub fn source_line_numbers(&self) -> Vec<usize> {
+        if !self.line_numbers.is_empty() {
+            return self.line_numbers.clone();
+        }
+
+        // content without recorded line numbers directly follows the start comment
+        (self.start_line + 1..)
+            .take(self.text.lines().count())
+            .collect()
+    }
 }
 
 /// Where to look for source snippets. Remote sources will be downloaded
@@ -34,6 +52,45 @@ pub struct Snippet {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(tag = "type")]
 pub enum SnippetSource {
+    /// Snippet source that comes from an archive (`.zip`, `.tar`, `.tar.gz` / `.tgz`) either on
+    /// the local file system or downloaded from a URL. Archives are unpacked into a temporary
+    /// directory on every run so their entries are always extracted and never recorded in the
+    /// index
+    Archive {
+        /// Local path or URL of the archive
+        path_or_url: String,
+        /// Glob patterns, matched against archive entry paths after `strip_prefix` is removed,
+        /// used to identify files to extract source snippets from
+        files: Vec<String>,
+        /// Leading path to remove from archive entries, e.g. a top-level `sdk-1.0.0/` directory
+        #[serde(skip_serializing_if = "Option::is_none")]
+        strip_prefix: Option<String>,
+        /// Handlebars template used to build snippet source links. `path`, `start_line` and
+        /// `end_line` are available to the template. Defaults to `path_or_url`.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        source_link_template: Option<String>,
+        /// Settings overridden for this source
+        #[serde(flatten)]
+        overrides: SourceOverrides,
+    },
+    /// Snippet source that runs a local command and extracts snippets from its stdout
+    Command {
+        /// Command to run
+        command: String,
+        /// Arguments passed to the command
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+        /// Working directory the command is run from. Defaults to the current directory
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cwd: Option<String>,
+        /// Language, name or file extension, of the command output. Used to determine snippet
+        /// comments and the `lang` attribute
+        #[serde(skip_serializing_if = "Option::is_none")]
+        language: Option<String>,
+        /// Settings overridden for this source
+        #[serde(flatten)]
+        overrides: SourceOverrides,
+    },
     /// Snippet source that comes from a remote Git repository
     Git {
         /// Git repository to clone
@@ -46,14 +103,106 @@ pub enum SnippetSource {
         cone_patterns: Option<Vec<String>>, // for sparse checkout. cone pattern sets
         /// Glob patterns used to identify files to extract source snippets from
         files: Vec<String>,
+        /// Glob patterns, relative to the repository root, of files and directories to exclude
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        exclude: Vec<String>,
+        /// Settings overridden for this source
+        #[serde(flatten)]
+        overrides: SourceOverrides,
     },
     /// Snippet source that comes from local files
     Local {
-        /// Glob patterns used to identify files to extract source snippets from
+        /// Glob patterns used to identify files to extract source snippets from. `-` reads from
+        /// stdin
         files: Vec<String>,
+        /// Glob patterns of files and directories to exclude
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        exclude: Vec<String>,
+        /// Language, name or file extension, of content read from stdin. Used to determine
+        /// snippet comments and the `lang` attribute
+        #[serde(skip_serializing_if = "Option::is_none")]
+        stdin_language: Option<String>,
+        /// Settings overridden for this source
+        #[serde(flatten)]
+        overrides: SourceOverrides,
     },
     /// Snippet source that comes from a URL
-    Url { url: String },
+    Url {
+        /// URL to download
+        url: String,
+        /// Settings overridden for this source
+        #[serde(flatten)]
+        overrides: SourceOverrides,
+    },
+}
+
+impl SnippetSource {
+    /// Create a local source for the given glob patterns
+    pub fn new_local(files: Vec<String>) -> Self {
+        SnippetSource::Local {
+            files,
+            exclude: Vec::new(),
+            stdin_language: None,
+            overrides: SourceOverrides::default(),
+        }
+    }
+
+    /// Create a git source for the given repository
+    pub fn new_git(
+        repository: String,
+        branch: Option<String>,
+        cone_patterns: Option<Vec<String>>,
+        files: Vec<String>,
+    ) -> Self {
+        SnippetSource::Git {
+            repository,
+            branch,
+            cone_patterns,
+            files,
+            exclude: Vec::new(),
+            overrides: SourceOverrides::default(),
+        }
+    }
+
+    /// Create a URL source
+    pub fn new_url(url: String) -> Self {
+        SnippetSource::Url {
+            url,
+            overrides: SourceOverrides::default(),
+        }
+    }
+
+    /// Settings overridden for this source
+    pub fn overrides(&self) -> &SourceOverrides {
+        match self {
+            SnippetSource::Archive { overrides, .. }
+            | SnippetSource::Command { overrides, .. }
+            | SnippetSource::Git { overrides, .. }
+            | SnippetSource::Local { overrides, .. }
+            | SnippetSource::Url { overrides, .. } => overrides,
+        }
+    }
+}
+
+/// Settings that can be overridden for an individual snippet source. Values that are not
+/// provided fall back to the global [`SnippextSettings`](crate::settings::SnippextSettings).
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct SourceOverrides {
+    /// Prefix that marks the start of a snippet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    /// Prefix that marks the end of a snippet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    /// Defines the format of snippet source links that appear under each snippet.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_format: Option<LinkFormat>,
+    /// String that will prefix all local snippet source links.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_link_prefix: Option<String>,
+    /// Determines whether nested snippet comments are included in parent snippets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retain_nested_snippet_comments: Option<bool>,
 }
 
 /// Defines the format of snippet source links that appear under each snippet.
@@ -151,10 +300,82 @@ impl fmt::Display for MissingSnippetsBehavior {
     }
 }
 
+/// Newline style used when writing target files
+#[non_exhaustive]
+#[remain::sorted]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, ValueEnum)]
+#[clap(rename_all = "lower")]
+pub enum LineEnding {
+    /// Windows style `\r\n` line endings
+    Crlf,
+    /// Unix style `\n` line endings
+    Lf,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Lf => "\n",
+        }
+    }
+}
+
+/// How much common leading whitespace is removed from rendered snippets. Either `true` to remove
+/// all of it, `false` to keep it or a maximum number of columns to remove.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Dedent {
+    Enabled(bool),
+    Columns(usize),
+}
+
+impl Default for Dedent {
+    fn default() -> Self {
+        Dedent::Enabled(true)
+    }
+}
+
+impl Dedent {
+    /// Maximum number of columns removed, None when all common indentation is removed
+    pub fn max_columns(&self) -> Option<usize> {
+        match self {
+            Dedent::Enabled(true) => None,
+            Dedent::Enabled(false) => Some(0),
+            Dedent::Columns(columns) => Some(*columns),
+        }
+    }
+}
+
+/// Template language used to render snippets
+#[non_exhaustive]
+#[remain::sorted]
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize, ValueEnum)]
+#[clap(rename_all = "lower")]
+pub enum TemplateEngine {
+    /// [Handlebars](https://handlebarsjs.com/) templates
+    #[default]
+    Handlebars,
+    /// Jinja2 templates rendered by [MiniJinja](https://github.com/mitsuhiko/minijinja)
+    MiniJinja,
+}
+
+impl TemplateEngine {
+    /// Engine for a template file extension, e.g. `hbs` or `j2`
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "handlebars" | "hbs" => Some(TemplateEngine::Handlebars),
+            "j2" | "jinja" | "jinja2" => Some(TemplateEngine::MiniJinja),
+            _ => None,
+        }
+    }
+}
+
+/// Snippet comments by file extension. Shared across the threads extracting snippets.
 pub(crate) struct SnippetCommentCache {
     start_prefix: String,
     end_prefix: String,
-    inner: RefCell<HashMap<String, SnippextComments>>,
+    inner: RwLock<HashMap<String, Arc<SnippextComments>>>,
 }
 
 impl SnippetCommentCache {
@@ -166,15 +387,27 @@ impl SnippetCommentCache {
         }
     }
 
-    // would prefer to return & or Ref instead of RefMut but doesn't look like that's an option
-    pub fn get(&self, extension: String) -> RefMut<SnippextComments> {
-        RefMut::map(self.inner.borrow_mut(), |map| {
-            map.entry(extension.clone())
-                .or_insert(SnippextComments::new(
-                    extension.clone().as_str(),
+    pub fn get(&self, extension: String) -> Arc<SnippextComments> {
+        if let Some(comments) = self
+            .inner
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&extension)
+        {
+            return comments.clone();
+        }
+
+        self.inner
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(extension.clone())
+            .or_insert_with(|| {
+                Arc::new(SnippextComments::new(
+                    extension.as_str(),
                     self.start_prefix.as_str(),
                     self.end_prefix.as_str(),
                 ))
-        })
+            })
+            .clone()
     }
 }
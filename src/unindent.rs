@@ -3,38 +3,132 @@
 use std::iter::Peekable;
 use std::slice::Split;
 
+use crate::constants::DEFAULT_TAB_WIDTH;
+
 pub(crate) fn unindent(s: &str) -> String {
+    unindent_with(s, UnindentOptions::default())
+}
+
+pub(crate) fn unindent_with(s: &str, options: UnindentOptions) -> String {
     let bytes = s.as_bytes();
-    let unindented = unindent_bytes(bytes);
+    let unindented = unindent_bytes_with(bytes, options);
     String::from_utf8(unindented).unwrap()
 }
 
-// Compute the maximal number of spaces that can be removed from every line, and remove them.
+/// Controls how leading whitespace is removed. Indentation is measured in columns where a tab
+/// advances to the next multiple of `tab_width`, so lines indented with a mix of tabs and spaces
+/// are dedented consistently.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct UnindentOptions {
+    /// Maximum number of columns removed from every line. Removes all common indentation if None
+    pub max_columns: Option<usize>,
+    pub tab_width: usize,
+    /// Replace tabs in the remaining indentation with spaces
+    pub expand_tabs: bool,
+}
+
+impl Default for UnindentOptions {
+    fn default() -> Self {
+        Self {
+            max_columns: None,
+            tab_width: DEFAULT_TAB_WIDTH,
+            expand_tabs: false,
+        }
+    }
+}
+
 pub(crate) fn unindent_bytes(s: &[u8]) -> Vec<u8> {
+    unindent_bytes_with(s, UnindentOptions::default())
+}
+
+// Compute the maximal number of columns that can be removed from every line, and remove them.
+pub(crate) fn unindent_bytes_with(s: &[u8], options: UnindentOptions) -> Vec<u8> {
+    let tab_width = options.tab_width.max(1);
+
     // Document may start either on the same line as opening quote or on the next line
     let ignore_first_line = s.starts_with(b"\n") || s.starts_with(b"\r\n");
 
-    // Largest number of spaces that can be removed from every
+    // Largest number of columns that can be removed from every
     // non-whitespace-only line after the first
-    let spaces = s.lines().filter_map(count_spaces).min().unwrap_or(0);
-
-    if spaces == 0 {
+    let columns = s
+        .lines()
+        .filter_map(|line| count_columns(line, tab_width))
+        .min()
+        .unwrap_or(0);
+    let columns = options.max_columns.map_or(columns, |max| columns.min(max));
+
+    if columns == 0 && !options.expand_tabs {
         return s.to_vec();
     }
 
+    // A leading blank line is only dropped when indentation is removed
+    let skip_first_line = ignore_first_line && columns > 0;
+
     let mut result = Vec::with_capacity(s.len());
     for (i, line) in s.lines().enumerate() {
-        if i > 1 || (i == 1 && !ignore_first_line) {
-            result.push(b'\n');
+        if i == 0 && skip_first_line {
+            continue;
         }
-        if line.len() > spaces {
-            // Whitespace-only lines may have fewer than the number of spaces being removed
-            result.extend_from_slice(&line[spaces..]);
+        if i > 1 || (i == 1 && !skip_first_line) {
+            result.push(b'\n');
         }
+        // Whitespace-only lines may have fewer than the number of columns being removed
+        dedent_line(line, columns, tab_width, options.expand_tabs, &mut result);
     }
     result
 }
 
+/// Append `line` to `result` without its first `columns` columns of indentation. A tab spanning the
+/// boundary is replaced by the spaces that remain of it.
+fn dedent_line(
+    line: &[u8],
+    columns: usize,
+    tab_width: usize,
+    expand_tabs: bool,
+    result: &mut Vec<u8>,
+) {
+    let mut column = 0;
+    let mut indent_end = 0;
+    for ch in line {
+        let next = match ch {
+            b' ' => column + 1,
+            b'\t' => (column / tab_width + 1) * tab_width,
+            _ => break,
+        };
+
+        indent_end += 1;
+        if next <= columns {
+            column = next;
+            continue;
+        }
+
+        let start = column.max(columns);
+        if expand_tabs || column < columns {
+            result.resize(result.len() + next - start, b' ');
+        } else {
+            result.push(*ch);
+        }
+        column = next;
+    }
+
+    result.extend_from_slice(&line[indent_end..]);
+}
+
+/// Width of the line's indentation in columns, or None if the line is entirely whitespace.
+fn count_columns(line: &[u8], tab_width: usize) -> Option<usize> {
+    let mut column = 0;
+    for ch in line {
+        match ch {
+            b' ' => column += 1,
+            b'\t' => column = (column / tab_width + 1) * tab_width,
+            b'\r' => {}
+            _ => return Some(column),
+        }
+    }
+    None
+}
+
+#[allow(dead_code)]
 pub(crate) trait Unindent {
     type Output;
 
@@ -65,7 +159,7 @@ impl Unindent for [u8] {
     }
 }
 
-impl<'a, T: ?Sized + Unindent> Unindent for &'a T {
+impl<T: ?Sized + Unindent> Unindent for &T {
     type Output = T::Output;
 
     fn unindent(&self) -> Self::Output {
@@ -73,27 +167,13 @@ impl<'a, T: ?Sized + Unindent> Unindent for &'a T {
     }
 }
 
-// Number of leading spaces in the line, or None if the line is entirely spaces.
-pub(crate) fn count_spaces(line: &[u8]) -> Option<usize> {
-    for (i, ch) in line.iter().enumerate() {
-        if *ch != b' ' && *ch != b'\t' {
-            return Some(i);
-        }
-    }
-    None
-}
-
-pub(crate) fn count_spaces_string<S: AsRef<str>>(line: S) -> Option<usize> {
-    count_spaces(line.as_ref().as_bytes())
-}
-
 // Based on core::str::StrExt.
 trait BytesExt {
-    fn lines(&self) -> Lines;
+    fn lines(&self) -> Lines<'_>;
 }
 
 impl BytesExt for [u8] {
-    fn lines(&self) -> Lines {
+    fn lines(&self) -> Lines<'_> {
         fn is_newline(b: &u8) -> bool {
             *b == b'\n'
         }
@@ -115,3 +195,54 @@ impl<'a> Iterator for Lines<'a> {
         self.split.next()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{unindent, unindent_with, UnindentOptions};
+
+    fn options(max_columns: Option<usize>, expand_tabs: bool) -> UnindentOptions {
+        UnindentOptions {
+            max_columns,
+            tab_width: 4,
+            expand_tabs,
+        }
+    }
+
+    #[test]
+    fn should_unindent_spaces() {
+        assert_eq!(
+            "fn main() {\n    run();\n}\n",
+            unindent("    fn main() {\n        run();\n    }\n")
+        );
+        assert_eq!("a\n\n  b", unindent("\n  a\n\n    b"));
+    }
+
+    #[test]
+    fn should_unindent_mixed_tabs_and_spaces() {
+        // a tab and four spaces are the same width
+        assert_eq!(
+            "func main() {\n\trun()\n}\n",
+            unindent("\tfunc main() {\n    \trun()\n    }\n")
+        );
+        // a tab spanning the removed columns keeps its remaining width as spaces
+        assert_eq!("a\n  b\n", unindent("  a\n\tb\n"));
+    }
+
+    #[test]
+    fn should_limit_removed_columns_and_expand_tabs() {
+        let text = "\t\tfunc main() {\n\t\t\trun()\n\t\t}\n";
+        assert_eq!(
+            "\tfunc main() {\n\t\trun()\n\t}\n",
+            unindent_with(text, options(Some(4), false))
+        );
+        assert_eq!(text, unindent_with(text, options(Some(0), false)));
+        assert_eq!(
+            "func main() {\n    run()\n}\n",
+            unindent_with(text, options(None, true))
+        );
+        assert_eq!(
+            "        func main() {\n",
+            unindent_with("\t\tfunc main() {\n", options(Some(0), true))
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use glob::{MatchOptions, Pattern};
+use ignore::{DirEntry, WalkBuilder};
+
+use crate::error::SnippextError;
+use crate::SnippextResult;
+
+/// Name of Snippext specific ignore files. Uses the same syntax as `.gitignore`
+pub const SNIPPEXT_IGNORE_FILE: &str = ".snippextignore";
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Walks source directories honoring `.gitignore`, `.ignore` and `.snippextignore` files as well
+/// as source `exclude` globs.
+#[derive(Clone)]
+pub(crate) struct SourceWalker {
+    respect_ignore_files: bool,
+    filter: Arc<ExcludeFilter>,
+}
+
+struct ExcludeFilter {
+    excludes: Vec<Pattern>,
+    /// Canonicalized directories that are always excluded, e.g. the output directory
+    excluded_dirs: Vec<PathBuf>,
+}
+
+impl SourceWalker {
+    pub fn new(
+        respect_ignore_files: bool,
+        exclude: &[String],
+        excluded_dirs: &[&str],
+    ) -> SnippextResult<Self> {
+        let excludes = exclude
+            .iter()
+            .map(|e| {
+                Pattern::new(e.trim_start_matches("./")).map_err(|err| {
+                    SnippextError::GlobPatternError(format!(
+                        "Glob pattern error for `{}`. {}",
+                        e, err.msg
+                    ))
+                })
+            })
+            .collect::<SnippextResult<Vec<Pattern>>>()?;
+
+        let excluded_dirs = excluded_dirs
+            .iter()
+            .filter_map(|d| fs::canonicalize(d).ok())
+            .collect();
+
+        Ok(Self {
+            respect_ignore_files,
+            filter: Arc::new(ExcludeFilter {
+                excludes,
+                excluded_dirs,
+            }),
+        })
+    }
+
+    /// Files matching the glob `pattern`. Only the pattern's literal base directory is walked.
+    pub fn glob(&self, pattern: &str) -> SnippextResult<Vec<PathBuf>> {
+        let glob_pattern = Pattern::new(pattern).map_err(|e| {
+            SnippextError::GlobPatternError(format!(
+                "Glob pattern error for `{}`. {}",
+                pattern, e.msg
+            ))
+        })?;
+
+        let (base, strip_base) = match literal_base(pattern) {
+            Some(base) => (PathBuf::from(base), false),
+            None => (PathBuf::from("."), true),
+        };
+
+        if !base.exists() {
+            return Ok(Vec::new());
+        }
+
+        Ok(self
+            .walk_from(&base, strip_base)
+            .into_iter()
+            .filter(|p| glob_pattern.matches_path_with(p, MATCH_OPTIONS))
+            // same as glob paths which don't include a leading `./`
+            .map(|p| p.strip_prefix("./").map(Path::to_path_buf).unwrap_or(p))
+            .collect())
+    }
+
+    /// All files under `root` with paths relative to `root`
+    pub fn walk(&self, root: &Path) -> Vec<PathBuf> {
+        self.walk_from(root, false)
+            .into_iter()
+            .filter_map(|p| p.strip_prefix(root).ok().map(Path::to_path_buf))
+            .collect()
+    }
+
+    fn walk_from(&self, root: &Path, strip_root: bool) -> Vec<PathBuf> {
+        let respect = self.respect_ignore_files;
+        let mut builder = WalkBuilder::new(root);
+        builder
+            .standard_filters(false)
+            .git_ignore(respect)
+            .git_global(respect)
+            .git_exclude(respect)
+            .ignore(respect)
+            .parents(respect)
+            .require_git(false)
+            .sort_by_file_name(|a, b| a.cmp(b));
+
+        if respect {
+            builder.add_custom_ignore_filename(SNIPPEXT_IGNORE_FILE);
+        }
+
+        let filter = self.filter.clone();
+        let root_path = root.to_path_buf();
+        builder.filter_entry(move |entry| {
+            if respect && entry.file_name() == ".git" {
+                return false;
+            }
+
+            let relative = entry
+                .path()
+                .strip_prefix(&root_path)
+                .unwrap_or(entry.path());
+            !filter.is_excluded(entry, relative, strip_root)
+        });
+
+        builder
+            .build()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+            .map(|e| {
+                let path = e.into_path();
+                if strip_root {
+                    path.strip_prefix(root)
+                        .map(Path::to_path_buf)
+                        .unwrap_or(path)
+                } else {
+                    path
+                }
+            })
+            .collect()
+    }
+}
+
+impl ExcludeFilter {
+    fn is_excluded(&self, entry: &DirEntry, relative: &Path, strip_root: bool) -> bool {
+        let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+        if is_dir && !self.excluded_dirs.is_empty() {
+            if let Ok(path) = fs::canonicalize(entry.path()) {
+                if self.excluded_dirs.iter().any(|d| d == &path) {
+                    return true;
+                }
+            }
+        }
+
+        if self.excludes.is_empty() {
+            return false;
+        }
+
+        // excludes are matched against the walked path, without any leading `./`, as well as the
+        // path relative to the walk root
+        let path = if strip_root {
+            relative.to_string_lossy().to_string()
+        } else {
+            entry.path().to_string_lossy().to_string()
+        };
+        let path = path.trim_start_matches("./");
+        let relative = relative.to_string_lossy();
+        let candidates = [path, relative.as_ref()];
+        self.excludes.iter().any(|exclude| {
+            candidates.iter().any(|candidate| {
+                exclude.matches_with(candidate, MATCH_OPTIONS)
+                    || (is_dir && exclude.matches_with(&format!("{}/", candidate), MATCH_OPTIONS))
+            })
+        })
+    }
+}
+
+/// Leading path components of a glob pattern that don't contain any glob special characters
+fn literal_base(pattern: &str) -> Option<&str> {
+    let Some(special) = pattern.find(['*', '?', '[']) else {
+        return Some(pattern);
+    };
+
+    match pattern[..special].rfind('/') {
+        Some(0) => Some("/"),
+        Some(i) => Some(&pattern[..i]),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use tempfile::tempdir;
+
+    use super::SourceWalker;
+
+    #[test]
+    fn literal_base() {
+        assert_eq!(Some("./src"), super::literal_base("./src/**/*.rs"));
+        assert_eq!(Some("/tmp"), super::literal_base("/tmp/*.rs"));
+        assert_eq!(Some("/"), super::literal_base("/*.rs"));
+        assert_eq!(Some("README.md"), super::literal_base("README.md"));
+        assert_eq!(None, super::literal_base("**"));
+    }
+
+    #[test]
+    fn should_honor_ignore_files_and_excludes() {
+        let dir = tempdir().unwrap();
+        for file in [
+            "src/main.rs",
+            "src/generated/api.rs",
+            "target/debug/build.rs",
+            "node_modules/pkg/index.js",
+            "docs/guide.md",
+            ".git/HEAD",
+        ] {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+        fs::write(dir.path().join(".snippextignore"), "node_modules/\n").unwrap();
+
+        let walker = SourceWalker::new(
+            true,
+            &["src/generated/**".to_string()],
+            &[dir.path().join("docs").to_str().unwrap()],
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                PathBuf::from(".gitignore"),
+                PathBuf::from(".snippextignore"),
+                PathBuf::from("src/main.rs"),
+            ],
+            walker.walk(dir.path())
+        );
+
+        let walker = SourceWalker::new(false, &[], &[]).unwrap();
+        assert_eq!(8, walker.walk(dir.path()).len());
+    }
+
+    #[test]
+    fn should_glob_from_literal_base() {
+        let walker = SourceWalker::new(true, &[], &[]).unwrap();
+        assert_eq!(
+            vec![
+                PathBuf::from("tests/samples/custom_markers.rb"),
+                PathBuf::from("tests/samples/custom_prefix.rb"),
+            ],
+            walker.glob("./tests/samples/*.rb").unwrap()
+        );
+        assert_eq!(
+            vec![PathBuf::from("tests/samples/main.rs")],
+            walker.glob("./tests/samples/main.rs").unwrap()
+        );
+    }
+}
@@ -1,12 +1,17 @@
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
 use indexmap::IndexMap;
 use snippext::cmd::extract::extract;
-use snippext::constants::{DEFAULT_SNIPPEXT_CONFIG, DEFAULT_TEMPLATE, DEFAULT_TEMPLATE_IDENTIFIER};
+use snippext::constants::{
+    DEFAULT_SNIPPEXT_CONFIG, DEFAULT_SOURCE_FILES, DEFAULT_TEMPLATE, DEFAULT_TEMPLATE_IDENTIFIER,
+    OUTPUT_MANIFEST_FILE,
+};
 use snippext::error::SnippextError;
 use snippext::settings::SnippextSettings;
-use snippext::types::{LinkFormat, SnippetSource};
+use snippext::types::{LineEnding, LinkFormat, SnippetSource, TemplateEngine};
 use tempfile::tempdir;
 use tracing_test::traced_test;
 use walkdir::WalkDir;
@@ -28,9 +33,9 @@ fn should_successfully_extract_from_local_sources_directory() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("{{snippet}}"),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/*")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/*",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         output_extension: Some(String::from("md")),
         ..Default::default()
@@ -83,9 +88,9 @@ fn should_successfully_extract_from_local_sources_directory_with_default_templat
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             DEFAULT_TEMPLATE.to_string(),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/*")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/*",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         output_extension: Some(String::from("md")),
         ..Default::default()
@@ -107,56 +112,1227 @@ fn main() {
 }
 
 #[test]
-#[traced_test]
-fn should_successfully_output_snippet_for_each_template() {
+#[traced_test]
+fn should_successfully_output_snippet_for_each_template() {
+    let dir = tempdir().unwrap();
+
+    extract(SnippextSettings {
+        templates: IndexMap::from([
+            (
+                DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+                String::from("```{{lang}}\n{{snippet}}```"),
+            ),
+            ("another_template".to_string(), String::from("{{snippet}}")),
+        ]),
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/main.rs",
+        )])],
+        output_dir: Some(dir.path().to_string_lossy().to_string()),
+        output_extension: Some(String::from("md")),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let count = WalkDir::new(Path::new(&dir.path()))
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.file_name() != OUTPUT_MANIFEST_FILE)
+        .count();
+
+    assert_eq!(4, count);
+
+    let main_default_content_actual =
+        fs::read_to_string(Path::new(&dir.path()).join("tests/samples/main.rs/main_default.md"))
+            .unwrap();
+    let main_default_content_expected = r#"```rust
+fn main() {
+
+    println!("printing...")
+}
+```"#;
+    assert_eq!(main_default_content_expected, main_default_content_actual);
+
+    let main_raw_content_actual = fs::read_to_string(
+        Path::new(&dir.path()).join("tests/samples/main.rs/main_another_template.md"),
+    )
+    .unwrap();
+    let main_raw_content_expected = r#"fn main() {
+
+    println!("printing...")
+}
+"#;
+    assert_eq!(main_raw_content_expected, main_raw_content_actual);
+}
+
+#[test]
+fn should_output_same_snippets_regardless_of_jobs() {
+    let extract_with_jobs = |jobs: usize| {
+        let dir = tempdir().unwrap();
+        extract(SnippextSettings {
+            sources: vec![SnippetSource::new_local(vec![String::from(
+                "./tests/samples/*",
+            )])],
+            output_dir: Some(dir.path().to_string_lossy().to_string()),
+            output_extension: Some(String::from("md")),
+            jobs: Some(jobs),
+            ..Default::default()
+        })
+        .unwrap();
+
+        WalkDir::new(dir.path())
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file())
+            .map(|e| {
+                (
+                    e.path().strip_prefix(dir.path()).unwrap().to_path_buf(),
+                    fs::read_to_string(e.path()).unwrap(),
+                )
+            })
+            .collect::<Vec<(PathBuf, String)>>()
+    };
+
+    let sequential = extract_with_jobs(1);
+    assert!(!sequential.is_empty());
+    assert_eq!(sequential, extract_with_jobs(4));
+}
+
+#[test]
+fn should_reuse_snippets_from_index_for_unchanged_sources() {
+    let dir = tempdir().unwrap();
+    let source = dir.path().join("main.rs");
+    let index_file = dir.path().join(".snippext/index.json");
+    fs::write(
+        &source,
+        "// snippet::start main\nfn main() {}\n// snippet::end\n",
+    )
+    .unwrap();
+
+    let settings = SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}"),
+        )]),
+        sources: vec![SnippetSource::new_local(vec![source
+            .to_string_lossy()
+            .to_string()])],
+        output_dir: Some(dir.path().join("out").to_string_lossy().to_string()),
+        output_extension: Some(String::from("md")),
+        index_file: Some(index_file.to_string_lossy().to_string()),
+        ..Default::default()
+    };
+
+    extract(settings.clone()).unwrap();
+    assert!(index_file.exists());
+
+    // same size and modification time so the source is considered unchanged
+    let modified = fs::metadata(&source).unwrap().modified().unwrap();
+    fs::write(
+        &source,
+        "// snippet::start main\nfn niam() {}\n// snippet::end\n",
+    )
+    .unwrap();
+    fs::File::options()
+        .write(true)
+        .open(&source)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+
+    let output = dir
+        .path()
+        .join("out")
+        .join(source.to_string_lossy().trim_start_matches('/'))
+        .join("main_default.md");
+
+    extract(settings.clone()).unwrap();
+    assert_eq!("fn main() {}\n", fs::read_to_string(&output).unwrap());
+
+    fs::remove_file(&index_file).unwrap();
+    extract(settings).unwrap();
+    assert_eq!("fn niam() {}\n", fs::read_to_string(&output).unwrap());
+}
+
+#[test]
+fn should_not_extract_from_index_file_with_default_sources() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("main.rs"),
+        "// snippet::start main\nfn main() {}\n// snippet::end\n",
+    )
+    .unwrap();
+
+    // default source glob with the default config's index file, .snippext/index.json
+    for _ in 0..2 {
+        let status = Command::new(env!("CARGO_BIN_EXE_snippext"))
+            .args([
+                "extract",
+                "--sources",
+                DEFAULT_SOURCE_FILES,
+                "--output-dir",
+                "generated-snippets",
+            ])
+            .current_dir(dir.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    let index: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join(".snippext/index.json")).unwrap())
+            .unwrap();
+    let sources = index["sources"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect::<Vec<String>>();
+    assert_eq!(vec!["main.rs"], sources);
+}
+
+#[test]
+fn should_exclude_ignored_files_and_output_dir() {
+    let dir = tempdir().unwrap();
+    let snippet = |id: &str| {
+        format!(
+            "// snippet::start {}\nfn {}() {{}}\n// snippet::end\n",
+            id, id
+        )
+    };
+    for (file, id) in [
+        ("src/main.rs", "main"),
+        ("src/generated/api.rs", "generated"),
+        ("target/debug/build.rs", "build"),
+    ] {
+        let path = dir.path().join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, snippet(id)).unwrap();
+    }
+    fs::write(dir.path().join(".gitignore"), "target/\n").unwrap();
+
+    let output_dir = dir.path().join("generated-snippets");
+    let sources: Vec<SnippetSource> = serde_json::from_value(serde_json::json!([{
+        "type": "Local",
+        "files": [format!("{}/**/*", dir.path().to_string_lossy())],
+        "exclude": ["**/generated/**"]
+    }]))
+    .unwrap();
+
+    let settings = SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}"),
+        )]),
+        sources,
+        output_dir: Some(output_dir.to_string_lossy().to_string()),
+        output_extension: Some(String::from("rs")),
+        ..Default::default()
+    };
+
+    let generated = |settings: SnippextSettings| {
+        extract(settings).unwrap();
+        let mut names = WalkDir::new(&output_dir)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file())
+            .filter(|e| e.file_name() != OUTPUT_MANIFEST_FILE)
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect::<Vec<String>>();
+        names.sort();
+        names
+    };
+
+    // running twice verifies output_dir isn't rescanned
+    assert_eq!(vec!["main_default.rs"], generated(settings.clone()));
+    assert_eq!(vec!["main_default.rs"], generated(settings.clone()));
+
+    assert_eq!(
+        vec!["build_default.rs", "main_default.rs"],
+        generated(SnippextSettings {
+            respect_ignore_files: false,
+            ..settings
+        })
+    );
+}
+
+#[test]
+fn should_skip_binary_and_oversized_files_and_decode_other_encodings() {
+    let dir = tempdir().unwrap();
+    let mut utf16 = vec![0xFF, 0xFE];
+    for unit in
+        "// snippet::start csharp\r\nConsole.WriteLine();\r\n// snippet::end\r\n".encode_utf16()
+    {
+        utf16.extend(unit.to_le_bytes());
+    }
+    fs::write(dir.path().join("main.cs"), utf16).unwrap();
+    fs::write(
+        dir.path().join("main.rb"),
+        b"# snippet::start latin1\nputs \"caf\xE9\"\n# snippet::end\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("fixture.db"),
+        b"SQLite format 3\x00// snippet::start binary\n// snippet::end\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("large.rs"),
+        format!(
+            "// snippet::start large\n{}\n// snippet::end\n",
+            "// filler".repeat(100)
+        ),
+    )
+    .unwrap();
+
+    let output_dir = dir.path().join("generated-snippets");
+    let settings = SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}"),
+        )]),
+        sources: vec![SnippetSource::new_local(vec![format!(
+            "{}/*",
+            dir.path().to_string_lossy()
+        )])],
+        output_dir: Some(output_dir.to_string_lossy().to_string()),
+        output_extension: Some(String::from("txt")),
+        max_file_size: Some(512),
+        ..Default::default()
+    };
+
+    extract(settings.clone()).unwrap();
+    let mut generated = WalkDir::new(&output_dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.file_name() != OUTPUT_MANIFEST_FILE)
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect::<Vec<String>>();
+    generated.sort();
+    assert_eq!(vec!["csharp_default.txt"], generated);
+
+    fs::remove_dir_all(&output_dir).unwrap();
+    extract(SnippextSettings {
+        fallback_encoding: Some(String::from("latin1")),
+        ..settings
+    })
+    .unwrap();
+
+    let latin1 = WalkDir::new(&output_dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .find(|e| e.file_name() == "latin1_default.txt")
+        .unwrap();
+    assert_eq!(
+        "puts \"café\"\n",
+        fs::read_to_string(latin1.path()).unwrap()
+    );
+}
+
+#[test]
+fn should_preserve_target_line_endings() {
+    let dir = tempdir().unwrap();
+    let target = dir.path().join("target.md");
+    fs::write(
+        &target,
+        "\u{FEFF}# README\r\n<!-- snippet::start fn_1 -->\r\n<!-- snippet::end -->\r\n",
+    )
+    .unwrap();
+
+    let settings = SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}"),
+        )]),
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/sample_file.rs",
+        )])],
+        output_dir: None,
+        targets: Some(vec![target.to_string_lossy().to_string()]),
+        ..Default::default()
+    };
+
+    extract(settings.clone()).unwrap();
+    assert_eq!(
+        "\u{FEFF}# README\r\n<!-- snippet::start fn_1 -->\r\nfn sample_fn_1() {\r\n\r\n}\r\n<!-- snippet::end -->\r\n",
+        fs::read_to_string(&target).unwrap()
+    );
+
+    extract(SnippextSettings {
+        line_endings: Some(LineEnding::Lf),
+        ..settings
+    })
+    .unwrap();
+    assert_eq!(
+        "\u{FEFF}# README\n<!-- snippet::start fn_1 -->\nfn sample_fn_1() {\n\n}\n<!-- snippet::end -->\n",
+        fs::read_to_string(&target).unwrap()
+    );
+}
+
+#[test]
+fn should_only_write_changed_files() {
+    let dir = tempdir().unwrap();
+    let target = dir.path().join("target.md");
+    fs::write(
+        &target,
+        "<!-- snippet::start fn_1 -->\n<!-- snippet::end -->\n",
+    )
+    .unwrap();
+
+    let output_dir = dir.path().join("generated-snippets");
+    let settings = SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}"),
+        )]),
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/sample_file.rs",
+        )])],
+        output_dir: Some(output_dir.to_string_lossy().to_string()),
+        output_extension: Some(String::from("md")),
+        targets: Some(vec![target.to_string_lossy().to_string()]),
+        ..Default::default()
+    };
+
+    let summary = extract(settings.clone()).unwrap();
+    assert_eq!(3, summary.changed_files.len());
+    assert_eq!(Some(&target), summary.changed_files.last());
+
+    let summary = extract(settings).unwrap();
+    assert!(summary.changed_files.is_empty());
+}
+
+#[test]
+fn should_preserve_target_marker_indentation_and_prefix() {
+    let dir = tempdir().unwrap();
+    let target = dir.path().join("target.md");
+    fs::write(
+        &target,
+        r#"1. Add a function
+
+   <!-- snippet::start fn_1 -->
+   <!-- snippet::end -->
+
+> <!-- snippet::start fn_1 -->
+> <!-- snippet::end -->
+
+* <!-- snippet::start fn_1 -->
+  <!-- snippet::end -->
+
+<!-- snippet::start fn_1 {"indent": 2, "prefix": "| "} -->
+<!-- snippet::end -->
+"#,
+    )
+    .unwrap();
+    let java_target = dir.path().join("Target.java");
+    fs::write(
+        &java_target,
+        r#"/**
+ * // snippet::start fn_1
+ * // snippet::end
+ */
+"#,
+    )
+    .unwrap();
+
+    extract(SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}"),
+        )]),
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/sample_file.rs",
+        )])],
+        output_dir: None,
+        targets: Some(vec![
+            target.to_string_lossy().to_string(),
+            java_target.to_string_lossy().to_string(),
+        ]),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let expected = r#"1. Add a function
+
+   <!-- snippet::start fn_1 -->
+   fn sample_fn_1() {
+
+   }
+   <!-- snippet::end -->
+
+> <!-- snippet::start fn_1 -->
+> fn sample_fn_1() {
+>
+> }
+> <!-- snippet::end -->
+
+* <!-- snippet::start fn_1 -->
+  fn sample_fn_1() {
+
+  }
+  <!-- snippet::end -->
+
+<!-- snippet::start fn_1 {"indent": 2, "prefix": "| "} -->
+  | fn sample_fn_1() {
+  |
+  | }
+<!-- snippet::end -->
+"#;
+    assert_eq!(expected, fs::read_to_string(&target).unwrap());
+
+    let expected = r#"/**
+ * // snippet::start fn_1
+ * fn sample_fn_1() {
+ *
+ * }
+ * // snippet::end
+ */
+"#;
+    assert_eq!(expected, fs::read_to_string(&java_target).unwrap());
+}
+
+#[test]
+fn should_ignore_markers_in_code_blocks() {
+    let dir = tempdir().unwrap();
+    let target = dir.path().join("target.md");
+    let content = r#"```md
+<!-- snippet::start fn_1 -->
+<!-- snippet::end -->
+```
+
+```md
+<!-- snippet::start fn_2 {"code_block": true} -->
+<!-- snippet::end -->
+```
+"#;
+    fs::write(&target, content).unwrap();
+
+    let settings = SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}"),
+        )]),
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/sample_file.rs",
+        )])],
+        output_dir: None,
+        targets: Some(vec![target.to_string_lossy().to_string()]),
+        ..Default::default()
+    };
+
+    extract(settings.clone()).unwrap();
+    let expected = r#"```md
+<!-- snippet::start fn_1 -->
+<!-- snippet::end -->
+```
+
+```md
+<!-- snippet::start fn_2 {"code_block": true} -->
+fn sample_fn_2() {
+
+}
+<!-- snippet::end -->
+```
+"#;
+    assert_eq!(expected, fs::read_to_string(&target).unwrap());
+
+    fs::write(&target, content).unwrap();
+    extract(SnippextSettings {
+        code_block_markers: true,
+        ..settings
+    })
+    .unwrap();
+    assert!(fs::read_to_string(&target)
+        .unwrap()
+        .contains("<!-- snippet::start fn_1 -->\nfn sample_fn_1() {"));
+}
+
+#[test]
+fn should_prune_stale_generated_files() {
+    let dir = tempdir().unwrap();
+    let source = dir.path().join("main.rs");
+    fs::write(
+        &source,
+        "// snippet::start main\nfn main() {}\n// snippet::end\n",
+    )
+    .unwrap();
+
+    let output_dir = dir.path().join("generated-snippets");
+    fs::create_dir_all(&output_dir).unwrap();
+    fs::write(output_dir.join("notes.md"), "not generated").unwrap();
+
+    let settings = SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}"),
+        )]),
+        sources: vec![SnippetSource::new_local(vec![source
+            .to_string_lossy()
+            .to_string()])],
+        output_dir: Some(output_dir.to_string_lossy().to_string()),
+        output_extension: Some(String::from("md")),
+        prune_output_dir: true,
+        ..Default::default()
+    };
+
+    let summary = extract(settings.clone()).unwrap();
+    let generated = summary.changed_files.first().unwrap().clone();
+    assert!(generated.ends_with("main_default.md"));
+
+    fs::write(
+        &source,
+        "// snippet::start run\nfn run() {}\n// snippet::end\n",
+    )
+    .unwrap();
+    let summary = extract(settings).unwrap();
+    assert_eq!(vec![generated.clone()], summary.removed_files);
+    assert!(!generated.exists());
+    assert!(output_dir.join("notes.md").exists());
+    assert!(output_dir.join(OUTPUT_MANIFEST_FILE).exists());
+}
+
+#[test]
+fn should_support_output_path_template() {
+    let dir = tempdir().unwrap();
+    let output_dir = dir.path().join("generated-snippets");
+    let settings = SnippextSettings {
+        templates: IndexMap::from([
+            (
+                DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+                String::from("```{{lang}}\n{{snippet}}```\n"),
+            ),
+            (String::from("raw"), String::from("{{snippet}}")),
+        ]),
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/sample_file.rs",
+        )])],
+        output_dir: Some(output_dir.to_string_lossy().to_string()),
+        output_extension: Some(String::from("md")),
+        output_extensions: IndexMap::from([(String::from("raw"), String::from("rs"))]),
+        output_path_template: Some(String::from("{{template}}/{{id}}.{{ext}}")),
+        ..Default::default()
+    };
+
+    extract(settings.clone()).unwrap();
+    assert_eq!(
+        "fn sample_fn_1() {\n\n}\n",
+        fs::read_to_string(output_dir.join("raw/fn_1.rs")).unwrap()
+    );
+    assert!(output_dir.join("default/fn_2.md").exists());
+
+    let result = extract(SnippextSettings {
+        output_path_template: Some(String::from("../{{id}}.{{ext}}")),
+        ..settings
+    });
+    assert!(matches!(result, Err(SnippextError::GeneralError(_))));
+    assert!(!dir.path().join("fn_1.md").exists());
+}
+
+#[test]
+fn should_generate_snippets_index() {
+    let dir = tempdir().unwrap();
+    let output_dir = dir.path().join("generated-snippets");
+    let settings = SnippextSettings {
+        templates: IndexMap::from([
+            (
+                DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+                String::from("{{snippet}}"),
+            ),
+            (String::from("raw"), String::from("{{snippet}}")),
+        ]),
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/sample_file.rs",
+        )])],
+        output_dir: Some(output_dir.to_string_lossy().to_string()),
+        output_extension: Some(String::from("md")),
+        generate_snippets_index: true,
+        generate_markdown_index: true,
+        ..Default::default()
+    };
+
+    extract(settings).unwrap();
+
+    let index: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(output_dir.join("snippets.json")).unwrap())
+            .unwrap();
+    let snippets = index["snippets"].as_array().unwrap();
+    assert_eq!(2, snippets.len());
+    assert_eq!("fn_1", snippets[0]["id"]);
+    assert_eq!("tests/samples/sample_file.rs", snippets[0]["path"]);
+    assert_eq!("rust", snippets[0]["lang"]);
+    assert_eq!(
+        "tests/samples/sample_file.rs/fn_1_raw.md",
+        snippets[0]["files"]["raw"]
+    );
+    assert!(output_dir
+        .join(snippets[0]["files"]["default"].as_str().unwrap())
+        .exists());
+
+    let markdown = fs::read_to_string(output_dir.join("index.md")).unwrap();
+    assert!(markdown.contains("| `fn_2` |"));
+
+    let manifest = fs::read_to_string(output_dir.join(OUTPUT_MANIFEST_FILE)).unwrap();
+    assert!(manifest.contains("snippets.json"));
+}
+
+#[test]
+fn should_generate_snippet_files_for_each_language_sharing_an_id() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("main.rs"),
+        r#"use std::net::TcpStream;
+
+fn main() {
+    // snippet::start connect
+    let stream = TcpStream::connect("127.0.0.1:8080").unwrap();
+    // snippet::end
+    println!("{:?}", stream.peer_addr());
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("main.py"),
+        r#"import socket
+
+def main():
+    # snippet::start connect
+    stream = socket.create_connection(("127.0.0.1", 8080))
+    # snippet::end
+    print(stream.getpeername())
+"#,
+    )
+    .unwrap();
+
+    let output_dir = dir.path().join("out");
+    let settings = SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}"),
+        )]),
+        sources: vec![SnippetSource::new_local(vec![dir
+            .path()
+            .join("main.*")
+            .to_string_lossy()
+            .to_string()])],
+        output_dir: Some(output_dir.to_string_lossy().to_string()),
+        output_extension: Some(String::from("md")),
+        generate_snippets_index: true,
+        ..Default::default()
+    };
+
+    extract(settings).unwrap();
+
+    let index: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(output_dir.join("snippets.json")).unwrap())
+            .unwrap();
+    let snippets = index["snippets"].as_array().unwrap();
+    assert_eq!(2, snippets.len());
+    for snippet in snippets {
+        assert_eq!("connect", snippet["id"]);
+        assert!(output_dir
+            .join(snippet["files"]["default"].as_str().unwrap())
+            .exists());
+    }
+
+    for source in ["main.rs", "main.py"] {
+        let source_path = dir.path().join(source).to_string_lossy().to_string();
+        assert!(output_dir
+            .join(source_path.trim_start_matches(['.', '/']))
+            .join("connect_default.md")
+            .exists());
+    }
+}
+
+#[test]
+fn error_when_snippet_output_conflicts_with_snippets_index() {
+    let dir = tempdir().unwrap();
+    let source = dir.path().join("main.rs");
+    fs::write(
+        &source,
+        "// snippet::start index\nfn main() {}\n// snippet::end\n",
+    )
+    .unwrap();
+
+    let settings = SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}"),
+        )]),
+        sources: vec![SnippetSource::new_local(vec![source
+            .to_string_lossy()
+            .to_string()])],
+        output_dir: Some(dir.path().join("out").to_string_lossy().to_string()),
+        output_extension: Some(String::from("md")),
+        output_path_template: Some(String::from("{{id}}.{{ext}}")),
+        generate_snippets_index: true,
+        generate_markdown_index: true,
+        ..Default::default()
+    };
+
+    let error = extract(settings).unwrap_err();
+    assert!(error
+        .to_string()
+        .ends_with("index.md conflicts with the snippets index"));
+    assert!(!dir.path().join("out/snippets.json").exists());
+}
+
+#[test]
+fn should_support_template_partials_and_layouts() {
+    let dir = tempdir().unwrap();
+    let output_dir = dir.path().join("generated-snippets");
+    let settings = SnippextSettings {
+        templates: IndexMap::from([
+            (
+                DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+                String::from("{{#> _layout}}```{{lang}}\n{{snippet}}```\n{{/_layout}}"),
+            ),
+            (
+                String::from("_layout"),
+                String::from("{{> @partial-block}}\n{{> _source_link}}"),
+            ),
+            (
+                String::from("_source_link"),
+                String::from("<a href='{{source_path}}'>snippet source</a>\n"),
+            ),
+        ]),
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/sample_file.rs",
+        )])],
+        output_dir: Some(output_dir.to_string_lossy().to_string()),
+        output_extension: Some(String::from("md")),
+        ..Default::default()
+    };
+
+    extract(settings).unwrap();
+
+    let generated_dir = output_dir.join("tests/samples/sample_file.rs");
+    assert_eq!(
+        "```rust\nfn sample_fn_1() {\n\n}\n```\n<a href='tests/samples/sample_file.rs'>snippet source</a>\n",
+        fs::read_to_string(generated_dir.join("fn_1_default.md")).unwrap()
+    );
+
+    let mut generated = fs::read_dir(&generated_dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .collect::<Vec<String>>();
+    generated.sort();
+    assert_eq!(vec!["fn_1_default.md", "fn_2_default.md"], generated);
+}
+
+#[test]
+fn should_render_minijinja_templates() {
+    let dir = tempdir().unwrap();
+    let output_dir = dir.path().join("generated-snippets");
+    let settings = SnippextSettings {
+        templates: IndexMap::from([
+            (
+                DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+                String::from("```{{lang}}\n{{snippet}}```\n"),
+            ),
+            (
+                String::from("html"),
+                String::from(
+                    "{% extends \"_layout\" %}{% block code %}{{ snippet | line_numbers | escape_html }}{% endblock %}",
+                ),
+            ),
+            (
+                String::from("_layout"),
+                String::from(
+                    "<pre class=\"{{ lang | lang_alias }}\">\n{% block code %}{% endblock %}</pre>\n",
+                ),
+            ),
+        ]),
+        template_engines: IndexMap::from([
+            (String::from("html"), TemplateEngine::MiniJinja),
+            (String::from("_layout"), TemplateEngine::MiniJinja),
+        ]),
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/sample_file.rs",
+        )])],
+        output_dir: Some(output_dir.to_string_lossy().to_string()),
+        output_extension: Some(String::from("md")),
+        ..Default::default()
+    };
+
+    extract(settings.clone()).unwrap();
+
+    let generated_dir = output_dir.join("tests/samples/sample_file.rs");
+    assert_eq!(
+        "<pre class=\"rust\">\n1 fn sample_fn_1() {\n2 \n3 }\n</pre>\n",
+        fs::read_to_string(generated_dir.join("fn_1_html.md")).unwrap()
+    );
+    assert_eq!(
+        "```rust\nfn sample_fn_1() {\n\n}\n```\n",
+        fs::read_to_string(generated_dir.join("fn_1_default.md")).unwrap()
+    );
+
+    let mut templates = settings.templates.clone();
+    templates.insert(
+        String::from("html"),
+        String::from("{% if lang %}{{ lang }}"),
+    );
+    let result = extract(SnippextSettings {
+        templates,
+        ..settings
+    });
+    match result {
+        Err(SnippextError::ValidationError(failures)) => {
+            assert_eq!(1, failures.len());
+            assert!(
+                failures[0].starts_with("templates[html] is not a valid template at line 1."),
+                "{}",
+                failures[0]
+            );
+        }
+        _ => panic!("expected validation error"),
+    }
+}
+
+#[test]
+fn should_support_template_presets() {
+    let dir = tempdir().unwrap();
+    let target = dir.path().join("target.adoc");
+    fs::write(
+        &target,
+        "// snippet::start fn_1 {\"template\": \"preset:asciidoc\", \"title\": \"Sample\"}\n// snippet::end\n",
+    )
+    .unwrap();
+
+    let output_dir = dir.path().join("generated-snippets");
+    let settings = SnippextSettings {
+        templates: IndexMap::from([
+            (
+                DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+                String::from("preset:markdown"),
+            ),
+            (String::from("rst"), String::from("preset:rst")),
+        ]),
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/sample_file.rs",
+        )])],
+        output_dir: Some(output_dir.to_string_lossy().to_string()),
+        output_extensions: IndexMap::from([(String::from("rst"), String::from("rst"))]),
+        targets: Some(vec![target.to_string_lossy().to_string()]),
+        omit_source_links: true,
+        ..Default::default()
+    };
+
+    extract(settings.clone()).unwrap();
+
+    let generated_dir = output_dir.join("tests/samples/sample_file.rs");
+    assert_eq!(
+        "```rust\nfn sample_fn_1() {\n\n}\n```\n",
+        fs::read_to_string(generated_dir.join("fn_1_default.md")).unwrap()
+    );
+    assert_eq!(
+        ".. code-block:: rust\n\n   fn sample_fn_1() {\n\n   }\n\n",
+        fs::read_to_string(generated_dir.join("fn_1_rst.rst")).unwrap()
+    );
+    assert_eq!(
+        "// snippet::start fn_1 {\"template\": \"preset:asciidoc\", \"title\": \"Sample\"}\n.Sample\n[source,rust]\n----\nfn sample_fn_1() {\n\n}\n----\n// snippet::end\n",
+        fs::read_to_string(&target).unwrap()
+    );
+
+    let result = extract(SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("preset:unknown"),
+        )]),
+        ..settings
+    });
+    assert!(matches!(result, Err(SnippextError::ValidationError(_))));
+}
+
+#[test]
+fn should_select_templates_by_target() {
+    let dir = tempdir().unwrap();
+    let adoc_target = dir.path().join("target.adoc");
+    let html_target = dir.path().join("docs/page.html");
+    let md_target = dir.path().join("target.md");
+    fs::create_dir_all(dir.path().join("docs")).unwrap();
+    fs::write(&adoc_target, "// snippet::start fn_1\n// snippet::end\n").unwrap();
+    fs::write(
+        &html_target,
+        "<!-- snippet::start fn_1 -->\n<!-- snippet::end -->\n",
+    )
+    .unwrap();
+    fs::write(
+        &md_target,
+        "<!-- snippet::start fn_1 {\"template\": \"html\"} -->\n<!-- snippet::end -->\n",
+    )
+    .unwrap();
+
+    let settings = SnippextSettings {
+        templates: IndexMap::from([
+            (
+                DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+                String::from("{{snippet}}"),
+            ),
+            (
+                String::from("html"),
+                String::from("<pre>{{snippet}}</pre>\n"),
+            ),
+        ]),
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/sample_file.rs",
+        )])],
+        targets: Some(vec![
+            dir.path().join("*.*").to_string_lossy().to_string(),
+            dir.path().join("docs/*.html").to_string_lossy().to_string(),
+        ]),
+        target_templates: IndexMap::from([
+            (String::from(".adoc"), String::from("preset:asciidoc")),
+            (String::from("**/docs/*.html"), String::from("html")),
+        ]),
+        omit_source_links: true,
+        ..Default::default()
+    };
+
+    extract(settings.clone()).unwrap();
+
+    assert_eq!(
+        "// snippet::start fn_1\n[source,rust]\n----\nfn sample_fn_1() {\n\n}\n----\n// snippet::end\n",
+        fs::read_to_string(&adoc_target).unwrap()
+    );
+    assert_eq!(
+        "<!-- snippet::start fn_1 -->\n<pre>fn sample_fn_1() {\n\n}\n</pre>\n<!-- snippet::end -->\n",
+        fs::read_to_string(&html_target).unwrap()
+    );
+    // the template attribute takes precedence over the target mapping
+    assert_eq!(
+        "<!-- snippet::start fn_1 {\"template\": \"html\"} -->\n<pre>fn sample_fn_1() {\n\n}\n</pre>\n<!-- snippet::end -->\n",
+        fs::read_to_string(&md_target).unwrap()
+    );
+
+    let result = extract(SnippextSettings {
+        target_templates: IndexMap::from([(String::from("adoc"), String::from("missing"))]),
+        ..settings
+    });
+    assert!(matches!(result, Err(SnippextError::ValidationError(_))));
+}
+
+#[test]
+fn should_render_snippet_groups() {
+    let dir = tempdir().unwrap();
+    let sources_dir = dir.path().join("sources");
+    fs::create_dir_all(&sources_dir).unwrap();
+    fs::write(
+        sources_dir.join("a_connect.rs"),
+        "fn main() {\n    // snippet::start connect\n    let client = Client::connect();\n    // snippet::end\n}\n",
+    )
+    .unwrap();
+    fs::write(
+        sources_dir.join("b_connect.py"),
+        "# snippet::start connect\nconnect()\n# snippet::end\n",
+    )
+    .unwrap();
+    fs::write(
+        sources_dir.join("c_connect.go"),
+        "// snippet::start connect.go {\"tab\": \"Golang\"}\nConnect()\n// snippet::end\n",
+    )
+    .unwrap();
+
+    let target = dir.path().join("target.md");
+    fs::write(
+        &target,
+        "<!-- snippet::start {\"group\": \"connect\"} -->\n<!-- snippet::end -->\n\
+         <!-- snippet::start connect {\"group\": true, \"template\": \"preset:asciidoc-tabs\"} -->\n<!-- snippet::end -->\n",
+    )
+    .unwrap();
+
+    extract(SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}"),
+        )]),
+        sources: vec![SnippetSource::new_local(vec![sources_dir
+            .join("*")
+            .to_string_lossy()
+            .to_string()])],
+        targets: Some(vec![target.to_string_lossy().to_string()]),
+        group_separator: Some(String::from(".")),
+        omit_source_links: true,
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert_eq!(
+        "<!-- snippet::start {\"group\": \"connect\"} -->\n\
+         **Rust**\n\n```rust\nlet client = Client::connect();\n```\n\n\
+         **Python**\n\n```python\nconnect()\n```\n\n\
+         **Golang**\n\n```go\nConnect()\n```\n\
+         <!-- snippet::end -->\n\
+         <!-- snippet::start connect {\"group\": true, \"template\": \"preset:asciidoc-tabs\"} -->\n\
+         [tabs]\n====\n\
+         Rust::\n+\n[source,rust]\n----\nlet client = Client::connect();\n----\n\
+         Python::\n+\n[source,python]\n----\nconnect()\n----\n\
+         Golang::\n+\n[source,go]\n----\nConnect()\n----\n\
+         ====\n<!-- snippet::end -->\n",
+        fs::read_to_string(&target).unwrap()
+    );
+}
+
+#[test]
+fn should_render_source_line_numbers() {
+    let dir = tempdir().unwrap();
+    let target = dir.path().join("target.md");
+    fs::write(
+        &target,
+        "<!-- snippet::start main {\"highlight\": \"1\"} -->\n<!-- snippet::end -->\n\
+         <!-- snippet::start main {\"selected_lines\": [\"1\", \"4\"], \"selected_lines_include_ellipses\": true} -->\n<!-- snippet::end -->\n\
+         <!-- snippet::start nested {\"template\": \"preset:markdown\", \"line_numbers\": true} -->\n<!-- snippet::end -->\n",
+    )
+    .unwrap();
+
+    extract(SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{#each lines}}{{number}}|{{text}}|{{highlighted}}\n{{/each}}"),
+        )]),
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/main.rs",
+        )])],
+        targets: Some(vec![target.to_string_lossy().to_string()]),
+        omit_source_links: true,
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert_eq!(
+        "<!-- snippet::start main {\"highlight\": \"1\"} -->\n\
+         2|fn main() {|true\n3||false\n5|    println!(\"printing...\")|false\n7|}|false\n\
+         <!-- snippet::end -->\n\
+         <!-- snippet::start main {\"selected_lines\": [\"1\", \"4\"], \"selected_lines_include_ellipses\": true} -->\n\
+         2|fn main() {|false\n|// ...|false\n7|}|false\n\
+         <!-- snippet::end -->\n\
+         <!-- snippet::start nested {\"template\": \"preset:markdown\", \"line_numbers\": true} -->\n\
+         ```rust\n5 println!(\"printing...\")\n```\n\
+         <!-- snippet::end -->\n",
+        fs::read_to_string(&target).unwrap()
+    );
+}
+
+#[test]
+fn should_only_number_contiguous_lines_from_line_start() {
     let dir = tempdir().unwrap();
+    let target = dir.path().join("target.md");
+    // main omits the nested snippet's comments so its lines aren't contiguous
+    fs::write(
+        &target,
+        "<!-- snippet::start main {\"template\": \"preset:docusaurus\"} -->\n<!-- snippet::end -->\n\
+         <!-- snippet::start main {\"template\": \"preset:markdown\"} -->\n<!-- snippet::end -->\n\
+         <!-- snippet::start nested {\"template\": \"preset:docusaurus\"} -->\n<!-- snippet::end -->\n",
+    )
+    .unwrap();
 
     extract(SnippextSettings {
-        templates: IndexMap::from([
-            (
-                DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
-                String::from("```{{lang}}\n{{snippet}}```"),
-            ),
-            ("another_template".to_string(), String::from("{{snippet}}")),
-        ]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/main.rs")],
-        }],
-        output_dir: Some(dir.path().to_string_lossy().to_string()),
-        output_extension: Some(String::from("md")),
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/main.rs",
+        )])],
+        targets: Some(vec![target.to_string_lossy().to_string()]),
+        omit_source_links: true,
+        line_numbers: true,
         ..Default::default()
     })
     .unwrap();
 
-    let count = WalkDir::new(Path::new(&dir.path()))
-        .into_iter()
-        .filter_map(Result::ok)
-        .filter(|e| e.file_type().is_file())
-        .count();
+    assert_eq!(
+        "<!-- snippet::start main {\"template\": \"preset:docusaurus\"} -->\n\
+         ```rust title=\"tests/samples/main.rs\"\nfn main() {\n\n    println!(\"printing...\")\n}\n```\n\
+         <!-- snippet::end -->\n\
+         <!-- snippet::start main {\"template\": \"preset:markdown\"} -->\n\
+         ```rust\n2 fn main() {\n3 \n5     println!(\"printing...\")\n7 }\n```\n\
+         <!-- snippet::end -->\n\
+         <!-- snippet::start nested {\"template\": \"preset:docusaurus\"} -->\n\
+         ```rust title=\"tests/samples/main.rs\" showLineNumbers=5\nprintln!(\"printing...\")\n```\n\
+         <!-- snippet::end -->\n",
+        fs::read_to_string(&target).unwrap()
+    );
+}
 
-    assert_eq!(4, count);
+#[test]
+fn should_dedent_tab_indented_snippets() {
+    let dir = tempdir().unwrap();
+    let source = dir.path().join("main.go");
+    fs::write(
+        &source,
+        "func main() {\n\t// snippet::start run\n\tfor {\n\t\trun()\n\t}\n\t// snippet::end\n}\n",
+    )
+    .unwrap();
 
-    let main_default_content_actual =
-        fs::read_to_string(Path::new(&dir.path()).join("tests/samples/main.rs/main_default.md"))
-            .unwrap();
-    let main_default_content_expected = r#"```rust
-fn main() {
+    let target = dir.path().join("target.md");
+    fs::write(
+        &target,
+        "<!-- snippet::start run -->\n<!-- snippet::end -->\n\
+         <!-- snippet::start run {\"dedent\": false} -->\n<!-- snippet::end -->\n\
+         <!-- snippet::start run {\"expand_tabs\": true} -->\n<!-- snippet::end -->\n",
+    )
+    .unwrap();
 
-    println!("printing...")
+    let settings = SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}"),
+        )]),
+        sources: vec![SnippetSource::new_local(vec![source
+            .to_string_lossy()
+            .to_string()])],
+        targets: Some(vec![target.to_string_lossy().to_string()]),
+        omit_source_links: true,
+        tab_width: 2,
+        ..Default::default()
+    };
+    extract(settings.clone()).unwrap();
+
+    assert_eq!(
+        "<!-- snippet::start run -->\nfor {\n\trun()\n}\n<!-- snippet::end -->\n\
+         <!-- snippet::start run {\"dedent\": false} -->\n\tfor {\n\t\trun()\n\t}\n<!-- snippet::end -->\n\
+         <!-- snippet::start run {\"expand_tabs\": true} -->\nfor {\n  run()\n}\n<!-- snippet::end -->\n",
+        fs::read_to_string(&target).unwrap()
+    );
+
+    let result = extract(SnippextSettings {
+        tab_width: 0,
+        ..settings
+    });
+    assert!(matches!(result, Err(SnippextError::ValidationError(_))));
 }
-```"#;
-    assert_eq!(main_default_content_expected, main_default_content_actual);
 
-    let main_raw_content_actual = fs::read_to_string(
-        Path::new(&dir.path()).join("tests/samples/main.rs/main_another_template.md"),
+#[test]
+fn should_extract_from_stdin_with_language() {
+    let dir = tempdir().unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_snippext"))
+        .args([
+            "extract",
+            "--sources",
+            "-",
+            "--stdin-language",
+            "Python",
+            "--output-dir",
+        ])
+        .arg(dir.path().join("generated-snippets"))
+        .current_dir(dir.path())
+        .stdin(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"# snippet::start hello\nprint(\"hello\")\n# snippet::end\n")
+        .unwrap();
+    assert!(child.wait().unwrap().success());
+
+    let actual = fs::read_to_string(
+        Path::new(&dir.path()).join("generated-snippets/stdin/hello_default.md"),
     )
     .unwrap();
-    let main_raw_content_expected = r#"fn main() {
-
-    println!("printing...")
-}
-"#;
-    assert_eq!(main_raw_content_expected, main_raw_content_actual);
+    assert!(actual.starts_with("```python\nprint(\"hello\")\n```\n"));
 }
 
 #[test]
@@ -168,12 +1344,12 @@ fn error_when_extracting_from_unavailable_remote() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("{{snippet}}"),
         )]),
-        sources: vec![SnippetSource::Git {
-            repository: String::from("https://some_bad_url_that_doesnt_exist.blah/not_found.git"),
-            branch: Some(String::from("main")),
-            cone_patterns: None,
-            files: vec![String::from("/tests/**/*")],
-        }],
+        sources: vec![SnippetSource::new_git(
+            String::from("https://some_bad_url_that_doesnt_exist.blah/not_found.git"),
+            Some(String::from("main")),
+            None,
+            vec![String::from("/tests/**/*")],
+        )],
         output_dir: Some(format!(
             "{}/generated-snippets/",
             dir.path().to_string_lossy()
@@ -194,9 +1370,9 @@ fn should_error_when_snippet_is_not_closed() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("{{snippet}}"),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/snippet_left_open.rs")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/snippet_left_open.rs",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         output_extension: Some(String::from("md")),
         ..Default::default()
@@ -220,12 +1396,12 @@ fn should_successfully_extract_from_remote_git_repository() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("{{snippet}}{{source_link}}"),
         )]),
-        sources: vec![SnippetSource::Git {
-            repository: String::from("https://github.com/doctavious/snippext.git"),
-            branch: Some(String::from("main")),
-            cone_patterns: None,
-            files: vec![String::from("/tests/samples/*")],
-        }],
+        sources: vec![SnippetSource::new_git(
+            String::from("https://github.com/doctavious/snippext.git"),
+            Some(String::from("main")),
+            None,
+            vec![String::from("/tests/samples/*")],
+        )],
         output_dir: Some(format!(
             "{}/generated-snippets/",
             dir.path().to_string_lossy()
@@ -255,12 +1431,12 @@ fn should_successfully_extract_from_remote_without_branch_provided() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("{{snippet}}"),
         )]),
-        sources: vec![SnippetSource::Git {
-            repository: String::from("https://github.com/doctavious/snippext.git"),
-            branch: None,
-            cone_patterns: None,
-            files: vec![String::from("/tests/samples/*")],
-        }],
+        sources: vec![SnippetSource::new_git(
+            String::from("https://github.com/doctavious/snippext.git"),
+            None,
+            None,
+            vec![String::from("/tests/samples/*")],
+        )],
         output_dir: Some(format!(
             "{}/generated-snippets/",
             dir.path().to_string_lossy()
@@ -292,9 +1468,7 @@ fn url_source_link() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("{{snippet}}{{source_link}}"),
         )]),
-        sources: vec![SnippetSource::Url {
-            url: "https://gist.githubusercontent.com/seancarroll/94629074d8cb36e9f5a0bc47b72ba6a5/raw/2b9d5db6482c7ff90a0cf3689d2a36b99e77d189/snippext_example.rs".into()
-        }],
+        sources: vec![SnippetSource::new_url("https://gist.githubusercontent.com/seancarroll/94629074d8cb36e9f5a0bc47b72ba6a5/raw/2b9d5db6482c7ff90a0cf3689d2a36b99e77d189/snippext_example.rs".into())],
         output_dir: Some(format!(
             "{}/generated-snippets/",
             dir.path().to_string_lossy()
@@ -329,9 +1503,9 @@ fn should_successfully_extract_from_local_sources_file() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("{{snippet}}"),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/custom_prefix.rb")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/custom_prefix.rb",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         output_extension: Some(String::from("md")),
         ..Default::default()
@@ -357,9 +1531,9 @@ fn should_update_specified_targets() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("{{snippet}}"),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/*")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/*",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         output_extension: Some(String::from("md")),
         targets: Some(vec![target.to_string_lossy().to_string()]),
@@ -381,7 +1555,8 @@ fn main() {
 fn sample_fn_1() {
 
 }
-<!-- snippet::end -->"#;
+<!-- snippet::end -->
+"#;
     assert_eq!(expected, actual);
 }
 
@@ -396,9 +1571,9 @@ fn should_keep_default_content_in_target_when_snippet_key_is_not_found() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("{{snippet}}"),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/main.rs")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/main.rs",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         output_extension: Some(String::from("md")),
         targets: Some(vec![target.to_string_lossy().to_string()]),
@@ -418,7 +1593,8 @@ fn main() {
 
 <!-- snippet::start fn_1 -->
 some content
-<!-- snippet::end -->"#;
+<!-- snippet::end -->
+"#;
     assert_eq!(expected, actual);
 }
 
@@ -431,9 +1607,9 @@ fn should_support_template_with_attributes() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("```{{lang}}\n{{snippet}}```\n"),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/main.rs")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/main.rs",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         output_extension: Some(String::from("md")),
         ..Default::default()
@@ -470,9 +1646,9 @@ fn support_target_snippet_specifies_template() {
                 String::from("```{{lang}}\n{{snippet}}```\n"),
             ),
         ]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/main.rs")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/main.rs",
+        )])],
         targets: Some(vec![target.to_string_lossy().to_string()]),
         ..Default::default()
     })
@@ -487,7 +1663,8 @@ fn main() {
     println!("printing...")
 }
 ```
-<!-- snippet::end -->"#;
+<!-- snippet::end -->
+"#;
     assert_eq!(expected, actual);
 }
 
@@ -508,9 +1685,9 @@ fn should_support_selected_lines() {
                 String::from("```{{lang}}\n{{snippet}}```\n"),
             ),
         ]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/main.rs")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/main.rs",
+        )])],
         targets: Some(vec![target.to_string_lossy().to_string()]),
         ..Default::default()
     })
@@ -524,7 +1701,8 @@ fn main() {
     println!("printing...")
 }
 ```
-<!-- snippet::end -->"#;
+<!-- snippet::end -->
+"#;
     assert_eq!(expected, actual);
 }
 
@@ -549,9 +1727,9 @@ fn should_support_selected_lines_with_ellipsis() {
                 String::from("```{{lang}}\n{{snippet}}```\n"),
             ),
         ]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/main.rs")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/main.rs",
+        )])],
         targets: Some(vec![target.to_string_lossy().to_string()]),
         ..Default::default()
     })
@@ -566,7 +1744,8 @@ fn main() {
     println!("printing...")
 }
 ```
-<!-- snippet::end -->"#;
+<!-- snippet::end -->
+"#;
     assert_eq!(expected, actual);
 }
 
@@ -579,9 +1758,9 @@ fn should_treat_unknown_template_variables_as_empty_string() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("```{{unknown}}\n{{snippet}}```\n"),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/main.rs")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/main.rs",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         output_extension: Some(String::from("md")),
         ..Default::default()
@@ -601,6 +1780,27 @@ fn main() {
     assert_eq!(expected, actual);
 }
 
+#[test]
+fn should_error_on_unknown_template_variables_in_strict_mode() {
+    let dir = tempdir().unwrap();
+
+    let result = extract(SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("```{{unknown}}\n{{snippet}}```\n"),
+        )]),
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/main.rs",
+        )])],
+        output_dir: Some(dir.path().to_string_lossy().to_string()),
+        output_extension: Some(String::from("md")),
+        strict_templates: true,
+        ..Default::default()
+    });
+
+    assert!(matches!(result, Err(SnippextError::TemplateRenderError(_))));
+}
+
 #[test]
 fn should_support_files_with_no_snippets() {
     let dir = tempdir().unwrap();
@@ -610,9 +1810,9 @@ fn should_support_files_with_no_snippets() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("{{snippet}}"),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/no_snippets.rs")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/no_snippets.rs",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         output_extension: Some(String::from("md")),
         ..Default::default()
@@ -621,7 +1821,6 @@ fn should_support_files_with_no_snippets() {
 
     let files: Vec<PathBuf> = fs::read_dir(&dir)
         .unwrap()
-        .into_iter()
         .filter(|r| r.is_ok())
         .map(|r| r.unwrap().path())
         .filter(|r| r.is_dir())
@@ -639,9 +1838,7 @@ fn invalid_glob() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("{{snippet}}"),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("[&")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from("[&")])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         output_extension: Some(String::from("md")),
         ..Default::default()
@@ -667,9 +1864,9 @@ fn glob_returns_no_files() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("{{snippet}}"),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/*.md")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/*.md",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         output_extension: Some(String::from("md")),
         ..Default::default()
@@ -678,7 +1875,6 @@ fn glob_returns_no_files() {
 
     let files: Vec<PathBuf> = fs::read_dir(&dir)
         .unwrap()
-        .into_iter()
         .filter(|r| r.is_ok())
         .map(|r| r.unwrap().path())
         .filter(|r| r.is_dir())
@@ -698,9 +1894,9 @@ fn support_source_links() {
                 "```{{snippet}}```{{#unless omit_source_link}}\n{{source_link}}{{/unless}}",
             ),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/custom_prefix.rb")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/custom_prefix.rb",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         output_extension: Some(String::from("md")),
         link_format: Some(LinkFormat::GitHub),
@@ -730,9 +1926,9 @@ fn source_links_should_support_prefix() {
                 "```{{snippet}}```{{#unless omit_source_link}}\n{{source_link}}{{/unless}}",
             ),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/custom_prefix.rb")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/custom_prefix.rb",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         output_extension: Some(String::from("md")),
         link_format: Some(LinkFormat::GitHub),
@@ -752,6 +1948,60 @@ fn source_links_should_support_prefix() {
     );
 }
 
+#[test]
+fn should_support_source_overrides() {
+    let dir = tempdir().unwrap();
+
+    let sources: Vec<SnippetSource> = serde_json::from_value(serde_json::json!([
+        {
+            "type": "Local",
+            "files": ["./tests/samples/custom_markers.rb"],
+            "start": "tag::",
+            "end": "end::",
+            "source_link_prefix": "http://github.com/bar"
+        },
+        {
+            "type": "Local",
+            "files": ["./tests/samples/custom_prefix.rb"]
+        }
+    ]))
+    .unwrap();
+
+    extract(SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from(
+                "```{{snippet}}```{{#unless omit_source_link}}\n{{source_link}}{{/unless}}",
+            ),
+        )]),
+        sources,
+        output_dir: Some(dir.path().to_string_lossy().to_string()),
+        output_extension: Some(String::from("md")),
+        link_format: Some(LinkFormat::GitHub),
+        source_link_prefix: Some("http://github.com/foo".into()),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let overridden = fs::read_to_string(
+        Path::new(&dir.path()).join("tests/samples/custom_markers.rb/ruby_default.md"),
+    )
+    .unwrap();
+    assert_eq!(
+        "```puts \"Hello, Ruby!\"\n```\nhttp://github.com/bar/tests/samples/custom_markers.rb#L1-L3",
+        overridden
+    );
+
+    let global = fs::read_to_string(
+        Path::new(&dir.path()).join("tests/samples/custom_prefix.rb/ruby_default.md"),
+    )
+    .unwrap();
+    assert_eq!(
+        "```puts \"Hello, Ruby!\"\n```\nhttp://github.com/foo/tests/samples/custom_prefix.rb#L2-L4",
+        global
+    );
+}
+
 #[test]
 fn omit_source_links() {
     let dir = tempdir().unwrap();
@@ -763,9 +2013,9 @@ fn omit_source_links() {
                 "```{{snippet}}```{{#unless omit_source_link}}\n{{source_link}}{{/unless}}",
             ),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/custom_prefix.rb")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/custom_prefix.rb",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         output_extension: Some(String::from("md")),
         link_format: Some(LinkFormat::GitHub),
@@ -793,9 +2043,9 @@ fn support_csharp_regions() {
                 "```{{snippet}}```{{#unless omit_source_link}}\n{{source_link}}{{/unless}}",
             ),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/main.cs")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/main.cs",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         output_extension: Some(String::from("md")),
         link_format: Some(LinkFormat::GitHub),
@@ -822,9 +2072,9 @@ fn should_retain_nested_snippet_comments() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("```{{lang}}\n{{snippet}}```\n"),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/main.rs")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/main.rs",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         retain_nested_snippet_comments: true,
         output_extension: Some(String::from("md")),
@@ -856,9 +2106,9 @@ fn should_retain_nested_snippet_comments_for_individual_snippets() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("```{{lang}}\n{{snippet}}```\n"),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/retain_nested_comments.rs")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/retain_nested_comments.rs",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         retain_nested_snippet_comments: false,
         output_extension: Some(String::from("md")),
@@ -909,9 +2159,9 @@ fn should_allow_custom_highlighted_attributes() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("```{{lang}} hl_lines=\"{{highlighted_lines}}\"\n{{snippet}}```\n"),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/main.rs")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/main.rs",
+        )])],
         targets: Some(vec![target.to_string_lossy().to_string()]),
         ..Default::default()
     })
@@ -926,7 +2176,8 @@ fn main() {
     println!("printing...")
 }
 ```
-<!-- snippet::end -->"#;
+<!-- snippet::end -->
+"#;
     assert_eq!(expected, actual);
 }
 
@@ -939,9 +2190,9 @@ fn should_autodetect_language() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("```{{lang}}\n{{snippet}}```\n"),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/custom_prefix.rb")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/custom_prefix.rb",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         retain_nested_snippet_comments: true,
         output_extension: Some(String::from("md")),
@@ -969,9 +2220,9 @@ fn should_support_disabling_autodetect_language() {
             DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
             String::from("```{{lang}}\n{{snippet}}```\n"),
         )]),
-        sources: vec![SnippetSource::Local {
-            files: vec![String::from("./tests/samples/custom_prefix.rb")],
-        }],
+        sources: vec![SnippetSource::new_local(vec![String::from(
+            "./tests/samples/custom_prefix.rb",
+        )])],
         output_dir: Some(dir.path().to_string_lossy().to_string()),
         retain_nested_snippet_comments: true,
         output_extension: Some(String::from("md")),
@@ -990,3 +2241,202 @@ puts "Hello, Ruby!"
 "#;
     assert_eq!(expected, actual);
 }
+
+const ARCHIVE_MAIN_RS: &str = r#"// snippet::start main
+fn main() {
+    println!("Hello from the SDK!");
+}
+// snippet::end
+"#;
+
+#[test]
+fn should_extract_from_tar_gz_archive() {
+    let dir = tempdir().unwrap();
+    let archive_path = dir.path().join("sdk-1.0.0.tar.gz");
+
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        fs::File::create(&archive_path).unwrap(),
+        flate2::Compression::default(),
+    ));
+    let mut header = tar::Header::new_gnu();
+    header.set_size(ARCHIVE_MAIN_RS.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder
+        .append_data(
+            &mut header,
+            "sdk-1.0.0/src/main.rs",
+            ARCHIVE_MAIN_RS.as_bytes(),
+        )
+        .unwrap();
+    builder.into_inner().unwrap().finish().unwrap();
+
+    extract(SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}{{source_link}}"),
+        )]),
+        sources: vec![SnippetSource::Archive {
+            path_or_url: archive_path.to_string_lossy().to_string(),
+            files: vec![String::from("src/**/*.rs")],
+            strip_prefix: Some(String::from("sdk-1.0.0/")),
+            source_link_template: Some(String::from(
+                "https://example.com/sdk/v1.0.0/{{path}}#L{{start_line}}-L{{end_line}}",
+            )),
+            overrides: Default::default(),
+        }],
+        output_dir: Some(format!(
+            "{}/generated-snippets/",
+            dir.path().to_string_lossy()
+        )),
+        output_extension: Some(String::from("md")),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let actual = fs::read_to_string(
+        Path::new(&dir.path()).join("generated-snippets/src/main.rs/main_default.md"),
+    )
+    .unwrap();
+
+    let expected = r#"fn main() {
+    println!("Hello from the SDK!");
+}
+https://example.com/sdk/v1.0.0/src/main.rs#L1-L5"#;
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn should_strip_archive_prefix_by_path_components() {
+    let dir = tempdir().unwrap();
+    let archive_path = dir.path().join("sdk-1.0.0.tar.gz");
+
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        fs::File::create(&archive_path).unwrap(),
+        flate2::Compression::default(),
+    ));
+    for path in ["sdk-1.0.0/src/main.rs", "sdk-1.0.0-extra/src/extra.rs"] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(ARCHIVE_MAIN_RS.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, ARCHIVE_MAIN_RS.as_bytes())
+            .unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap();
+
+    extract(SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}"),
+        )]),
+        sources: vec![SnippetSource::Archive {
+            path_or_url: archive_path.to_string_lossy().to_string(),
+            files: vec![String::from("**/*.rs")],
+            strip_prefix: Some(String::from("sdk-1.0.0")),
+            source_link_template: None,
+            overrides: Default::default(),
+        }],
+        output_dir: Some(format!(
+            "{}/generated-snippets/",
+            dir.path().to_string_lossy()
+        )),
+        output_extension: Some(String::from("md")),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let generated = WalkDir::new(dir.path().join("generated-snippets"))
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
+        .map(|e| {
+            e.path()
+                .strip_prefix(dir.path().join("generated-snippets"))
+                .unwrap()
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+        .collect::<Vec<String>>();
+    assert_eq!(vec!["src/main.rs/main_default.md"], generated);
+}
+
+#[test]
+fn should_extract_from_zip_archive() {
+    let dir = tempdir().unwrap();
+    let archive_path = dir.path().join("examples.zip");
+
+    let mut zip = zip::ZipWriter::new(fs::File::create(&archive_path).unwrap());
+    zip.start_file("src/main.rs", zip::write::FileOptions::default())
+        .unwrap();
+    std::io::Write::write_all(&mut zip, ARCHIVE_MAIN_RS.as_bytes()).unwrap();
+    zip.start_file("README.md", zip::write::FileOptions::default())
+        .unwrap();
+    zip.finish().unwrap();
+
+    extract(SnippextSettings {
+        templates: IndexMap::from([(
+            DEFAULT_TEMPLATE_IDENTIFIER.to_string(),
+            String::from("{{snippet}}{{source_link}}"),
+        )]),
+        sources: vec![SnippetSource::Archive {
+            path_or_url: archive_path.to_string_lossy().to_string(),
+            files: vec![String::from("**/*.rs")],
+            strip_prefix: None,
+            source_link_template: None,
+            overrides: Default::default(),
+        }],
+        output_dir: Some(format!(
+            "{}/generated-snippets/",
+            dir.path().to_string_lossy()
+        )),
+        output_extension: Some(String::from("md")),
+        ..Default::default()
+    })
+    .unwrap();
+
+    let actual = fs::read_to_string(
+        Path::new(&dir.path()).join("generated-snippets/src/main.rs/main_default.md"),
+    )
+    .unwrap();
+
+    let expected = format!(
+        "fn main() {{\n    println!(\"Hello from the SDK!\");\n}}\n{}",
+        archive_path.to_string_lossy()
+    );
+    assert_eq!(expected, actual);
+    assert!(!Path::new(&dir.path())
+        .join("generated-snippets/README.md")
+        .exists());
+}
+
+#[test]
+fn should_extract_from_stdin() {
+    let dir = tempdir().unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_snippext"))
+        .args(["extract", "--sources", "-", "--output-dir"])
+        .arg(dir.path().join("generated-snippets"))
+        .current_dir(dir.path())
+        .stdin(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(
+            fs::read("./tests/samples/sample_file.rs")
+                .unwrap()
+                .as_slice(),
+        )
+        .unwrap();
+    assert!(child.wait().unwrap().success());
+
+    let actual =
+        fs::read_to_string(Path::new(&dir.path()).join("generated-snippets/stdin/fn_1_raw.md"))
+            .unwrap();
+    assert_eq!("fn sample_fn_1() {\n\n}\n", actual);
+}
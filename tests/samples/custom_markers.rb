@@ -0,0 +1,3 @@
+# tag::ruby
+puts "Hello, Ruby!"
+# end::ruby